@@ -0,0 +1,304 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::assist::{AIR_JUMP_OPTIONS, UNLIMITED_AIR_JUMPS};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::settings::{GameSettings, SettingsChanged};
+use crate::states::GameState;
+use crate::widgets::{spawn_menu, MenuEntry, MenuEvent, MenuStyle, MenuSystems, UiTheme, Widget};
+
+/// Offered game speeds; physics slows down with everything else.
+pub const GAME_SPEEDS: [f32; 6] = [0.5, 0.6, 0.7, 0.8, 0.9, 1.0];
+
+/// Colors of the level and the player.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Blue, vermilion and yellow, told apart with deuteranopia and protanopia.
+    RedGreen,
+    /// Red, teal and white, told apart with tritanopia.
+    BlueYellow,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 3] = [Palette::Standard, Palette::RedGreen, Palette::BlueYellow];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Palette::Standard => "accessibility.palette_standard",
+            Palette::RedGreen => "accessibility.palette_red_green",
+            Palette::BlueYellow => "accessibility.palette_blue_yellow",
+        }
+    }
+
+    pub fn colors(self) -> WorldColors {
+        match self {
+            Palette::Standard => WorldColors {
+                player: Color::srgb(0.2, 0.4, 0.9),
+                platform: Color::srgb(0.4, 0.4, 0.4),
+                spikes: Color::srgb(0.9, 0.2, 0.2),
+                exit: Color::srgb(0.2, 0.9, 0.2),
+                hazard_mark: Color::BLACK,
+            },
+            Palette::RedGreen => WorldColors {
+                player: Color::srgb(0.0, 0.45, 0.7),
+                platform: Color::srgb(0.5, 0.5, 0.5),
+                spikes: Color::srgb(0.84, 0.37, 0.0),
+                exit: Color::srgb(0.94, 0.89, 0.26),
+                hazard_mark: Color::BLACK,
+            },
+            Palette::BlueYellow => WorldColors {
+                player: Color::srgb(0.9, 0.9, 0.9),
+                platform: Color::srgb(0.4, 0.4, 0.4),
+                spikes: Color::srgb(0.85, 0.15, 0.15),
+                exit: Color::srgb(0.0, 0.62, 0.45),
+                hazard_mark: Color::WHITE,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldColors {
+    pub player: Color,
+    pub platform: Color,
+    pub spikes: Color,
+    pub exit: Color,
+    /// The cross drawn over hazards when hazard shapes are on.
+    pub hazard_mark: Color,
+}
+
+/// Spawns a cross over a hazard tile of `size`, so it doesn't rely on color alone.
+pub fn spawn_hazard_mark(parent: &mut ChildSpawnerCommands, size: f32, color: Color) {
+    for angle in [45.0_f32, -45.0] {
+        parent.spawn((
+            Sprite {
+                color,
+                custom_size: Some(Vec2::new(size * 1.1, size * 0.15)),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, 0.1).with_rotation(Quat::from_rotation_z(angle.to_radians())),
+        ));
+    }
+}
+
+/// Tag of each row on the accessibility screen.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum AccessibilityRow {
+    Palette,
+    HazardShapes,
+    HighContrast,
+    ReducedMotion,
+    GameSpeed,
+    Invincible,
+    AirJumps,
+    LevelSkip,
+    Back,
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Accessibility), setup_accessibility)
+            .add_systems(
+                Update,
+                accessibility_action
+                    .in_set(MenuSystems::Respond)
+                    .run_if(in_state(GameState::Accessibility)),
+            )
+            .add_systems(
+                PostUpdate,
+                sync_ui_theme.run_if(resource_changed::<GameSettings>),
+            );
+    }
+}
+
+fn speed_index(speed: f32) -> usize {
+    GAME_SPEEDS
+        .iter()
+        .position(|s| (s - speed).abs() < 0.01)
+        .unwrap_or(GAME_SPEEDS.len() - 1)
+}
+
+fn air_jumps_text(count: u32, locale: &Locale) -> String {
+    match count {
+        0 => locale.get("settings.off").to_string(),
+        UNLIMITED_AIR_JUMPS => locale.get("accessibility.air_jumps_unlimited").to_string(),
+        count => count.to_string(),
+    }
+}
+
+fn accessibility_entries(settings: &GameSettings, locale: &Locale) -> Vec<MenuEntry<AccessibilityRow>> {
+    let palette_index = Palette::ALL.iter().position(|p| *p == settings.palette).unwrap_or(0);
+    let on_off = [locale.get("settings.off"), locale.get("settings.on")];
+    let air_jumps_index = AIR_JUMP_OPTIONS.iter().position(|n| *n == settings.air_jumps).unwrap_or(0);
+
+    vec![
+        MenuEntry::choice(
+            AccessibilityRow::Palette,
+            locale.get("accessibility.palette"),
+            Palette::ALL.iter().map(|p| locale.get(p.label_key()).to_string()).collect(),
+            palette_index,
+        ),
+        MenuEntry::toggle(
+            AccessibilityRow::HazardShapes,
+            locale.get("accessibility.hazard_shapes"),
+            settings.hazard_shapes,
+            on_off,
+        ),
+        MenuEntry::toggle(
+            AccessibilityRow::HighContrast,
+            locale.get("accessibility.high_contrast"),
+            settings.high_contrast,
+            on_off,
+        ),
+        MenuEntry::toggle(
+            AccessibilityRow::ReducedMotion,
+            locale.get("accessibility.reduced_motion"),
+            settings.reduced_motion,
+            on_off,
+        ),
+        MenuEntry::choice(
+            AccessibilityRow::GameSpeed,
+            locale.get("accessibility.game_speed"),
+            GAME_SPEEDS.iter().map(|s| format!("{}%", (s * 100.0).round())).collect(),
+            speed_index(settings.game_speed),
+        ),
+        // Assists: any of these keeps a level from setting records
+        MenuEntry::toggle(
+            AccessibilityRow::Invincible,
+            locale.get("accessibility.invincible"),
+            settings.invincible,
+            on_off,
+        )
+        .gap_before(20.0),
+        MenuEntry::choice(
+            AccessibilityRow::AirJumps,
+            locale.get("accessibility.air_jumps"),
+            AIR_JUMP_OPTIONS.iter().map(|n| air_jumps_text(*n, locale)).collect(),
+            air_jumps_index,
+        ),
+        MenuEntry::toggle(
+            AccessibilityRow::LevelSkip,
+            locale.get("accessibility.level_skip"),
+            settings.level_skip,
+            on_off,
+        ),
+        MenuEntry::button(AccessibilityRow::Back, locale.get("accessibility.back")).gap_before(20.0),
+    ]
+}
+
+fn setup_accessibility(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DespawnOnExit::<GameState>(GameState::Accessibility),
+        ))
+        .with_children(|parent| {
+            // Title
+            parent.spawn((
+                Text::new(locale.get("accessibility.title")),
+                fonts.title(40.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(30.0)),
+                    ..default()
+                },
+            ));
+
+            let style = MenuStyle {
+                item_width: 460.0,
+                item_height: 44.0,
+                font_size: 22.0,
+                spacing: 6.0,
+                ..default()
+            };
+            spawn_menu(parent, &fonts, style, accessibility_entries(&settings, &locale));
+        });
+}
+
+/// Changes count as unsaved settings; Save stays on the Settings screen.
+fn accessibility_action(
+    mut events: MessageReader<MenuEvent>,
+    rows: Query<(&AccessibilityRow, &Widget)>,
+    mut settings: ResMut<GameSettings>,
+    mut changed: ResMut<SettingsChanged>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        let item = match *event {
+            MenuEvent::Activated { item, .. } | MenuEvent::Changed { item, .. } => item,
+            MenuEvent::Back { .. } => {
+                next_state.set(GameState::Settings);
+                continue;
+            }
+        };
+        let Ok((row, widget)) = rows.get(item) else {
+            continue;
+        };
+
+        match (row, widget) {
+            (AccessibilityRow::Palette, Widget::Choice { index, .. }) => settings.palette = Palette::ALL[*index],
+            (AccessibilityRow::HazardShapes, Widget::Toggle { on, .. }) => settings.hazard_shapes = *on,
+            (AccessibilityRow::HighContrast, Widget::Toggle { on, .. }) => settings.high_contrast = *on,
+            (AccessibilityRow::ReducedMotion, Widget::Toggle { on, .. }) => settings.reduced_motion = *on,
+            (AccessibilityRow::GameSpeed, Widget::Choice { index, .. }) => settings.game_speed = GAME_SPEEDS[*index],
+            (AccessibilityRow::Invincible, Widget::Toggle { on, .. }) => settings.invincible = *on,
+            (AccessibilityRow::AirJumps, Widget::Choice { index, .. }) => settings.air_jumps = AIR_JUMP_OPTIONS[*index],
+            (AccessibilityRow::LevelSkip, Widget::Toggle { on, .. }) => settings.level_skip = *on,
+            (AccessibilityRow::Back, _) => {
+                next_state.set(GameState::Settings);
+                continue;
+            }
+            _ => continue,
+        }
+        changed.0 = true;
+    }
+}
+
+fn sync_ui_theme(settings: Res<GameSettings>, mut theme: ResMut<UiTheme>) {
+    theme.high_contrast = settings.high_contrast;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn palettes_keep_world_colors_apart() {
+        for palette in Palette::ALL {
+            let colors = palette.colors();
+            let parts = [colors.player, colors.platform, colors.spikes, colors.exit];
+            for (i, a) in parts.iter().enumerate() {
+                for b in &parts[i + 1..] {
+                    let (a, b) = (a.to_srgba(), b.to_srgba());
+                    let distance = Vec3::new(a.red - b.red, a.green - b.green, a.blue - b.blue).length();
+                    assert!(distance > 0.2, "{:?}: {:?} and {:?} are too close", palette, a, b);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_speed_falls_back_to_full() {
+        assert_eq!(speed_index(0.5), 0);
+        assert_eq!(speed_index(0.7), 2);
+        assert_eq!(speed_index(1.0), GAME_SPEEDS.len() - 1);
+        assert_eq!(speed_index(3.0), GAME_SPEEDS.len() - 1);
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::gamepad::GamepadConnectionEvent;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::fade::ScreenFade;
+use crate::progress::{data_dir, profile_dir};
+use crate::states::GameState;
+use crate::storage;
+
+/// How far the left stick has to be pushed to count as a direction.
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// Everything the game reacts to, independent of the key that triggers it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Pause,
+    SaveAttempt,
+    PlayReplay,
+    MenuUp,
+    MenuDown,
+    MenuLeft,
+    MenuRight,
+    Confirm,
+    Back,
+}
+
+/// Actions only conflict with others that are read at the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActionContext {
+    Gameplay,
+    Menu,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Pause,
+        Action::SaveAttempt,
+        Action::PlayReplay,
+        Action::MenuUp,
+        Action::MenuDown,
+        Action::MenuLeft,
+        Action::MenuRight,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    /// Locale key of the name shown in Controls.
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "action.move_left",
+            Action::MoveRight => "action.move_right",
+            Action::Jump => "action.jump",
+            Action::Pause => "action.pause",
+            Action::SaveAttempt => "action.save_attempt",
+            Action::PlayReplay => "action.play_replay",
+            Action::MenuUp => "action.menu_up",
+            Action::MenuDown => "action.menu_down",
+            Action::MenuLeft => "action.menu_left",
+            Action::MenuRight => "action.menu_right",
+            Action::Confirm => "action.confirm",
+            Action::Back => "action.back",
+        }
+    }
+
+    pub fn context(self) -> ActionContext {
+        match self {
+            Action::MoveLeft
+            | Action::MoveRight
+            | Action::Jump
+            | Action::Pause
+            | Action::SaveAttempt
+            | Action::PlayReplay => ActionContext::Gameplay,
+            _ => ActionContext::Menu,
+        }
+    }
+
+    /// Read on the same screen, so they can't share a key. The pause menu reads Pause
+    /// next to menu navigation; Pause and Back may share one, since both resume.
+    fn read_with(self, other: Action) -> bool {
+        let pause_menu = |a: Action, b: Action| {
+            a == Action::Pause && b.context() == ActionContext::Menu && b != Action::Back
+        };
+        self.context() == other.context() || pause_menu(self, other) || pause_menu(other, self)
+    }
+}
+
+/// Keys for each action. Stored in `keys.ron` next to `settings.ron`.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct KeyBindings(BTreeMap<Action, Vec<KeyCode>>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use KeyCode::*;
+        Self(BTreeMap::from([
+            (Action::MoveLeft, vec![KeyA, ArrowLeft]),
+            (Action::MoveRight, vec![KeyD, ArrowRight]),
+            (Action::Jump, vec![Space]),
+            (Action::Pause, vec![Escape]),
+            (Action::SaveAttempt, vec![F8]),
+            (Action::PlayReplay, vec![F9]),
+            (Action::MenuUp, vec![ArrowUp]),
+            (Action::MenuDown, vec![ArrowDown]),
+            (Action::MenuLeft, vec![ArrowLeft]),
+            (Action::MenuRight, vec![ArrowRight]),
+            (Action::Confirm, vec![Enter]),
+            (Action::Back, vec![Escape]),
+        ]))
+    }
+}
+
+impl KeyBindings {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Replaces all keys of `action` with `key`.
+    pub fn set(&mut self, action: Action, key: KeyCode) {
+        self.0.insert(action, vec![key]);
+    }
+
+    /// Another action read alongside `action` already using `key`.
+    pub fn conflict(&self, action: Action, key: KeyCode) -> Option<Action> {
+        Action::ALL
+            .into_iter()
+            .find(|other| *other != action && action.read_with(*other) && self.keys(*other).contains(&key))
+    }
+
+    pub fn pressed(&self, keyboard: &ButtonInput<KeyCode>, action: Action) -> bool {
+        self.keys(action).iter().any(|k| keyboard.pressed(*k))
+    }
+
+    pub fn just_pressed(&self, keyboard: &ButtonInput<KeyCode>, action: Action) -> bool {
+        self.keys(action).iter().any(|k| keyboard.just_pressed(*k))
+    }
+
+    /// Actions missing from an older file get their default keys.
+    fn fill_missing(&mut self) {
+        for (action, keys) in KeyBindings::default().0 {
+            self.0.entry(action).or_insert(keys);
+        }
+    }
+}
+
+/// Gamepad buttons are fixed; only the keyboard is rebindable.
+fn gamepad_buttons(action: Action) -> &'static [GamepadButton] {
+    match action {
+        Action::MoveLeft | Action::MenuLeft => &[GamepadButton::DPadLeft],
+        Action::MoveRight | Action::MenuRight => &[GamepadButton::DPadRight],
+        Action::MenuUp => &[GamepadButton::DPadUp],
+        Action::MenuDown => &[GamepadButton::DPadDown],
+        Action::Jump | Action::Confirm => &[GamepadButton::South],
+        Action::Back => &[GamepadButton::East],
+        Action::Pause => &[GamepadButton::Start],
+        Action::SaveAttempt | Action::PlayReplay => &[],
+    }
+}
+
+/// The left stick only moves the player; menus step with the d-pad.
+fn stick_pressed(gamepad: &Gamepad, action: Action) -> bool {
+    let x = gamepad.left_stick().x;
+    match action {
+        Action::MoveLeft => x < -STICK_THRESHOLD,
+        Action::MoveRight => x > STICK_THRESHOLD,
+        _ => false,
+    }
+}
+
+pub fn gamepad_pressed(gamepads: &Query<&Gamepad>, action: Action) -> bool {
+    gamepads.iter().any(|gamepad| {
+        gamepad_buttons(action).iter().any(|b| gamepad.pressed(*b)) || stick_pressed(gamepad, action)
+    })
+}
+
+pub fn gamepad_just_pressed(gamepads: &Query<&Gamepad>, action: Action) -> bool {
+    gamepads
+        .iter()
+        .any(|gamepad| gamepad_buttons(action).iter().any(|b| gamepad.just_pressed(*b)))
+}
+
+/// Keys as shown to the player: `KeyA` -> `A`, `Digit1` -> `1`.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+pub fn keys_label(keys: &[KeyCode]) -> String {
+    if keys.is_empty() {
+        return "-".to_string();
+    }
+    keys.iter().map(|k| key_name(*k)).collect::<Vec<_>>().join(" / ")
+}
+
+/// Read actions instead of raw keys: `actions.just_pressed(Action::Jump)`.
+/// Covers the keyboard and every connected gamepad.
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    bindings: Res<'w, KeyBindings>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+}
+
+impl Actions<'_, '_> {
+    pub fn pressed(&self, action: Action) -> bool {
+        self.bindings.pressed(&self.keyboard, action) || gamepad_pressed(&self.gamepads, action)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.bindings.just_pressed(&self.keyboard, action) || gamepad_just_pressed(&self.gamepads, action)
+    }
+}
+
+/// The controller last used. Losing it mid-level pauses the game.
+#[derive(Resource, Default)]
+pub struct ActiveGamepad(pub Option<Entity>);
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(load_bindings())
+            .init_resource::<ActiveGamepad>()
+            .add_systems(PreUpdate, (track_active_gamepad, pause_on_disconnect).chain());
+    }
+}
+
+fn track_active_gamepad(
+    mut connections: MessageReader<GamepadConnectionEvent>,
+    gamepads: Query<(Entity, &Gamepad)>,
+    mut active: ResMut<ActiveGamepad>,
+) {
+    for event in connections.read() {
+        if event.connected() && active.0.is_none() {
+            active.0 = Some(event.gamepad);
+        }
+    }
+    for (entity, gamepad) in &gamepads {
+        if gamepad.get_just_pressed().next().is_some() && active.0 != Some(entity) {
+            active.0 = Some(entity);
+        }
+    }
+}
+
+fn pause_on_disconnect(
+    mut connections: MessageReader<GamepadConnectionEvent>,
+    mut active: ResMut<ActiveGamepad>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    fades: Query<&ScreenFade>,
+) {
+    // Past the exit the level has already moved on; pausing would resume into the next one
+    let leaving = fades.iter().any(ScreenFade::is_fading_out);
+    for event in connections.read() {
+        if !event.disconnected() || active.0 != Some(event.gamepad) {
+            continue;
+        }
+        active.0 = None;
+        if *state.get() == GameState::Playing && !leaving {
+            info!("Controller disconnected, pausing");
+            next_state.set(GameState::Paused);
+        }
+    }
+}
+
+fn bindings_path() -> PathBuf {
+    profile_dir().join("keys.ron")
+}
+
+/// Key map shared by every profile before each had its own, still used by profiles
+/// that haven't changed any keys.
+fn legacy_bindings_path() -> PathBuf {
+    data_dir().join("keys.ron")
+}
+
+pub fn load_bindings() -> KeyBindings {
+    let path = bindings_path();
+    load_bindings_from(&if path.is_file() { path } else { legacy_bindings_path() })
+}
+
+fn load_bindings_from(path: &Path) -> KeyBindings {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return KeyBindings::default();
+    };
+    match ron::from_str::<KeyBindings>(&contents) {
+        Ok(mut bindings) => {
+            bindings.fill_missing();
+            bindings
+        }
+        Err(e) => {
+            error!("Failed to parse key bindings {:?}: {}", path, e);
+            KeyBindings::default()
+        }
+    }
+}
+
+pub fn save_bindings(bindings: &KeyBindings) {
+    save_bindings_to(bindings, &bindings_path());
+}
+
+fn save_bindings_to(bindings: &KeyBindings, path: &Path) {
+    match ron::ser::to_string_pretty(bindings, ron::ser::PrettyConfig::default()) {
+        Ok(text) => {
+            if let Err(e) = storage::write_atomic(path, text.as_bytes()) {
+                error!("Failed to save key bindings: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize key bindings: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::input::gamepad::GamepadConnection;
+
+    use crate::fade::spawn_screen_fade;
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let bindings = KeyBindings::default();
+        for action in Action::ALL {
+            assert!(!bindings.keys(action).is_empty(), "{:?} is unbound", action);
+            for key in bindings.keys(action) {
+                assert_eq!(bindings.conflict(action, *key), None);
+            }
+        }
+    }
+
+    #[test]
+    fn conflicts_between_actions_read_together() {
+        let bindings = KeyBindings::default();
+        assert_eq!(bindings.conflict(Action::Jump, KeyCode::KeyA), Some(Action::MoveLeft));
+        // Escape pauses the game and goes back in menus
+        assert_eq!(bindings.conflict(Action::Pause, KeyCode::Escape), None);
+        assert_eq!(bindings.conflict(Action::Back, KeyCode::Escape), None);
+        // ...but it would pause instead of confirming
+        assert_eq!(bindings.conflict(Action::Confirm, KeyCode::Escape), Some(Action::Pause));
+
+        // The pause menu reads Pause next to menu navigation
+        assert_eq!(bindings.conflict(Action::Pause, KeyCode::ArrowDown), Some(Action::MenuDown));
+        assert_eq!(bindings.conflict(Action::Pause, KeyCode::Enter), Some(Action::Confirm));
+        assert_eq!(bindings.conflict(Action::Jump, KeyCode::ArrowDown), None);
+    }
+
+    #[test]
+    fn bindings_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.ron");
+
+        let mut bindings = KeyBindings::default();
+        bindings.set(Action::Jump, KeyCode::KeyW);
+        save_bindings_to(&bindings, &path);
+        assert_eq!(load_bindings_from(&path), bindings);
+    }
+
+    #[test]
+    fn missing_actions_get_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.ron");
+        std::fs::write(&path, "({Jump: [KeyW]})").unwrap();
+
+        let bindings = load_bindings_from(&path);
+        assert_eq!(bindings.keys(Action::Jump), &[KeyCode::KeyW]);
+        assert_eq!(bindings.keys(Action::Back), &[KeyCode::Escape]);
+    }
+
+    #[test]
+    fn actions_follow_bindings() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        let mut bindings = KeyBindings::default();
+        bindings.set(Action::Jump, KeyCode::KeyW);
+        app.insert_resource(bindings);
+
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyW);
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ArrowLeft);
+
+        fn check(actions: Actions) -> (bool, bool, bool) {
+            (
+                actions.just_pressed(Action::Jump),
+                actions.pressed(Action::MoveLeft),
+                actions.pressed(Action::MoveRight),
+            )
+        }
+        let result = app.world_mut().run_system_once(check).unwrap();
+        assert_eq!(result, (true, true, false));
+    }
+
+    #[test]
+    fn gamepad_buttons_and_stick_map_to_actions() {
+        let mut app = App::new();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+
+        let mut gamepad = Gamepad::default();
+        gamepad.digital_mut().press(GamepadButton::South);
+        gamepad.analog_mut().set(GamepadAxis::LeftStickX, -0.8);
+        app.world_mut().spawn(gamepad);
+
+        fn check(actions: Actions) -> (bool, bool, bool, bool) {
+            (
+                actions.just_pressed(Action::Jump),
+                actions.just_pressed(Action::Confirm),
+                actions.pressed(Action::MoveLeft),
+                actions.pressed(Action::MenuLeft),
+            )
+        }
+        let result = app.world_mut().run_system_once(check).unwrap();
+        assert_eq!(result, (true, true, true, false));
+    }
+
+    #[test]
+    fn losing_active_gamepad_pauses() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin));
+        app.add_message::<GamepadConnectionEvent>();
+        app.insert_state(GameState::Playing);
+        app.add_systems(Update, pause_on_disconnect);
+
+        let other = app.world_mut().spawn_empty().id();
+        let active = app.world_mut().spawn_empty().id();
+        app.insert_resource(ActiveGamepad(Some(active)));
+
+        app.world_mut()
+            .write_message(GamepadConnectionEvent::new(other, GamepadConnection::Disconnected));
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Playing);
+
+        app.world_mut()
+            .write_message(GamepadConnectionEvent::new(active, GamepadConnection::Disconnected));
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Paused);
+        assert_eq!(app.world().resource::<ActiveGamepad>().0, None);
+    }
+
+    #[test]
+    fn disconnect_during_exit_fade_does_not_pause() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin));
+        app.add_message::<GamepadConnectionEvent>();
+        app.insert_state(GameState::Playing);
+        app.add_systems(Update, pause_on_disconnect);
+
+        let active = app.world_mut().spawn_empty().id();
+        app.insert_resource(ActiveGamepad(Some(active)));
+        let mut commands = app.world_mut().commands();
+        spawn_screen_fade(&mut commands, ScreenFade::to_black(0.4, GameState::Victory), GameState::Playing);
+        app.world_mut().flush();
+
+        app.world_mut()
+            .write_message(GamepadConnectionEvent::new(active, GamepadConnection::Disconnected));
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Playing);
+        assert_eq!(app.world().resource::<ActiveGamepad>().0, None);
+    }
+
+    #[test]
+    fn key_names() {
+        assert_eq!(key_name(KeyCode::KeyA), "A");
+        assert_eq!(key_name(KeyCode::Digit1), "1");
+        assert_eq!(key_name(KeyCode::Space), "Space");
+        assert_eq!(keys_label(&[KeyCode::KeyA, KeyCode::ArrowLeft]), "A / ArrowLeft");
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::HashMap;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::player::{Grounded, Player, PlayerDied};
+use crate::states::GameState;
+
+const PLAYER_ANIMATIONS: &str = "animations/player.ron";
+/// Horizontal speed above which the player counts as running and turns to face its direction.
+const RUN_SPEED: f32 = 10.0;
+/// Upward speed above which the player counts as airborne even while the ground
+/// sensor still touches the floor on the tick of a jump.
+const RISE_SPEED: f32 = 50.0;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnimationState {
+    Idle,
+    Run,
+    JumpRise,
+    Fall,
+    Land,
+    Death,
+}
+
+impl AnimationState {
+    pub const ALL: [AnimationState; 6] = [
+        AnimationState::Idle,
+        AnimationState::Run,
+        AnimationState::JumpRise,
+        AnimationState::Fall,
+        AnimationState::Land,
+        AnimationState::Death,
+    ];
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AnimationClip {
+    /// Sheet indices, played in order.
+    pub frames: Vec<usize>,
+    /// Seconds per frame.
+    pub frame_time: f32,
+    /// Clips that don't loop hold their last frame and report themselves finished.
+    #[serde(default)]
+    pub looping: bool,
+}
+
+/// The player's sprite sheet and clips, read from `assets/animations/player.ron`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PlayerAnimationData {
+    /// Path under `assets/`.
+    pub image: String,
+    pub frame_width: u32,
+    pub frame_height: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub clips: HashMap<AnimationState, AnimationClip>,
+}
+
+impl PlayerAnimationData {
+    /// Every state has a clip with at least one frame shown for some time, and every
+    /// frame is on the sheet.
+    fn validate(&self) -> Result<(), String> {
+        let frames = (self.columns * self.rows) as usize;
+        for state in AnimationState::ALL {
+            let Some(clip) = self.clips.get(&state) else {
+                return Err(format!("no clip for {:?}", state));
+            };
+            if clip.frames.is_empty() {
+                return Err(format!("clip {:?} has no frames", state));
+            }
+            // `advance` steps through frames until the elapsed time runs out
+            if clip.frame_time.is_nan() || clip.frame_time <= 0.0 {
+                return Err(format!("clip {:?}: frame time must be positive", state));
+            }
+            if let Some(frame) = clip.frames.iter().find(|frame| **frame >= frames) {
+                return Err(format!("clip {:?}: frame {} out of range", state, frame));
+            }
+        }
+        Ok(())
+    }
+
+    fn clip(&self, state: AnimationState) -> &AnimationClip {
+        &self.clips[&state]
+    }
+}
+
+/// Loaded player sheet. Missing when the file couldn't be read, and the player stays a plain rectangle.
+#[derive(Resource, Debug)]
+pub struct PlayerSheet {
+    pub data: PlayerAnimationData,
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+impl PlayerSheet {
+    /// First idle frame at `size`, tinted with the palette's player color.
+    pub fn sprite(&self, size: Vec2, tint: Color) -> Sprite {
+        Sprite {
+            color: tint,
+            custom_size: Some(size),
+            ..Sprite::from_atlas_image(
+                self.image.clone(),
+                TextureAtlas {
+                    layout: self.layout.clone(),
+                    index: self.data.clip(AnimationState::Idle).frames[0],
+                },
+            )
+        }
+    }
+}
+
+/// What the state machine looks at each frame.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Motion {
+    pub velocity: Vec2,
+    pub grounded: bool,
+    /// `PlayerDied` was written since the last frame.
+    pub died: bool,
+}
+
+/// The state to play next, given the current one and whether its clip has played out.
+pub fn next_state(current: AnimationState, finished: bool, motion: Motion) -> AnimationState {
+    if motion.died || (current == AnimationState::Death && !finished) {
+        return AnimationState::Death;
+    }
+    if !motion.grounded || motion.velocity.y > RISE_SPEED {
+        return if motion.velocity.y > 0.0 {
+            AnimationState::JumpRise
+        } else {
+            AnimationState::Fall
+        };
+    }
+    let running = motion.velocity.x.abs() > RUN_SPEED;
+    match current {
+        AnimationState::JumpRise | AnimationState::Fall => AnimationState::Land,
+        // Running cuts the landing short
+        AnimationState::Land if !finished && !running => AnimationState::Land,
+        _ if running => AnimationState::Run,
+        _ => AnimationState::Idle,
+    }
+}
+
+/// Whether the sprite should face left: turns with the horizontal velocity and keeps
+/// facing the same way while standing still.
+pub fn faces_left(facing_left: bool, velocity_x: f32) -> bool {
+    if velocity_x.abs() > RUN_SPEED {
+        velocity_x < 0.0
+    } else {
+        facing_left
+    }
+}
+
+/// Current state and playback position of the player's animation.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct PlayerAnimation {
+    pub state: AnimationState,
+    frame: usize,
+    elapsed: f32,
+}
+
+impl Default for PlayerAnimation {
+    fn default() -> Self {
+        Self {
+            state: AnimationState::Idle,
+            frame: 0,
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl PlayerAnimation {
+    /// Moves playback on by `delta` seconds; true once a clip that doesn't loop has played out.
+    pub fn advance(&mut self, clip: &AnimationClip, delta: f32) -> bool {
+        self.elapsed += delta;
+        while self.elapsed >= clip.frame_time {
+            self.elapsed -= clip.frame_time;
+            if self.frame + 1 < clip.frames.len() {
+                self.frame += 1;
+            } else if clip.looping {
+                self.frame = 0;
+            } else {
+                self.elapsed = 0.0;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Switches to `state`, starting its clip from the first frame if it's a different one.
+    pub fn set_state(&mut self, state: AnimationState) {
+        if state != self.state {
+            *self = Self {
+                state,
+                ..default()
+            };
+        }
+    }
+
+    /// Sheet index of the frame on screen.
+    pub fn sheet_index(&self, clip: &AnimationClip) -> usize {
+        clip.frames[self.frame.min(clip.frames.len() - 1)]
+    }
+}
+
+fn load_animation_data(path: &str) -> Option<PlayerAnimationData> {
+    let full_path = format!("assets/{}", path);
+    let contents = match std::fs::read_to_string(&full_path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Failed to read animations {}: {}", full_path, e);
+            return None;
+        }
+    };
+    let data: PlayerAnimationData = match ron::from_str(&contents) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Failed to parse animations {}: {}", full_path, e);
+            return None;
+        }
+    };
+    match data.validate() {
+        Ok(()) => Some(data),
+        Err(e) => {
+            warn!("Invalid animations {}: {}", full_path, e);
+            None
+        }
+    }
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, load_player_sheet).add_systems(
+            Update,
+            animate_player
+                .run_if(resource_exists::<PlayerSheet>)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn load_player_sheet(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let Some(data) = load_animation_data(PLAYER_ANIMATIONS) else {
+        return;
+    };
+    let layout = TextureAtlasLayout::from_grid(
+        UVec2::new(data.frame_width, data.frame_height),
+        data.columns,
+        data.rows,
+        None,
+        None,
+    );
+    commands.insert_resource(PlayerSheet {
+        image: asset_server.load(&data.image),
+        layout: layouts.add(layout),
+        data,
+    });
+}
+
+fn animate_player(
+    sheet: Res<PlayerSheet>,
+    time: Res<Time>,
+    mut died: MessageReader<PlayerDied>,
+    mut query: Query<(&mut PlayerAnimation, &mut Sprite, &LinearVelocity, &Grounded), With<Player>>,
+) {
+    let died = died.read().count() > 0;
+    for (mut animation, mut sprite, velocity, grounded) in &mut query {
+        let state = animation.state;
+        let finished = animation.advance(sheet.data.clip(state), time.delta_secs());
+        let motion = Motion {
+            velocity: velocity.0,
+            grounded: grounded.0,
+            died,
+        };
+        animation.set_state(next_state(state, finished, motion));
+
+        let index = animation.sheet_index(sheet.data.clip(animation.state));
+        if let Some(atlas) = sprite.texture_atlas.as_mut() {
+            atlas.index = index;
+        }
+        sprite.flip_x = faces_left(sprite.flip_x, velocity.x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grounded(velocity: Vec2) -> Motion {
+        Motion {
+            velocity,
+            grounded: true,
+            died: false,
+        }
+    }
+
+    fn airborne(velocity: Vec2) -> Motion {
+        Motion {
+            velocity,
+            grounded: false,
+            died: false,
+        }
+    }
+
+    #[test]
+    fn jump_rises_falls_and_lands() {
+        use AnimationState::*;
+        // The sensor still touches the floor on the tick of the jump
+        let state = next_state(Idle, false, grounded(Vec2::new(0.0, 500.0)));
+        assert_eq!(state, JumpRise);
+        let state = next_state(state, false, airborne(Vec2::new(0.0, 200.0)));
+        assert_eq!(state, JumpRise);
+        let state = next_state(state, false, airborne(Vec2::new(0.0, -100.0)));
+        assert_eq!(state, Fall);
+        let state = next_state(state, false, grounded(Vec2::ZERO));
+        assert_eq!(state, Land);
+        assert_eq!(next_state(state, false, grounded(Vec2::ZERO)), Land);
+        assert_eq!(next_state(state, true, grounded(Vec2::ZERO)), Idle);
+        assert_eq!(next_state(state, false, grounded(Vec2::new(300.0, 0.0))), Run);
+    }
+
+    #[test]
+    fn run_and_idle_follow_horizontal_speed() {
+        use AnimationState::*;
+        assert_eq!(next_state(Idle, false, grounded(Vec2::new(-300.0, 0.0))), Run);
+        assert_eq!(next_state(Run, false, grounded(Vec2::new(300.0, 0.0))), Run);
+        assert_eq!(next_state(Run, false, grounded(Vec2::ZERO)), Idle);
+        // Walking off a ledge
+        assert_eq!(next_state(Run, false, airborne(Vec2::new(300.0, -5.0))), Fall);
+    }
+
+    #[test]
+    fn death_plays_out_before_anything_else() {
+        use AnimationState::*;
+        let died = Motion {
+            died: true,
+            ..grounded(Vec2::ZERO)
+        };
+        assert_eq!(next_state(Run, false, died), Death);
+        assert_eq!(next_state(Death, false, grounded(Vec2::new(300.0, 0.0))), Death);
+        assert_eq!(next_state(Death, false, airborne(Vec2::new(0.0, -200.0))), Death);
+        assert_eq!(next_state(Death, true, airborne(Vec2::new(0.0, -200.0))), Fall);
+    }
+
+    #[test]
+    fn facing_turns_only_while_moving() {
+        assert!(faces_left(false, -300.0));
+        assert!(faces_left(true, 0.0));
+        assert!(!faces_left(true, 300.0));
+        assert!(!faces_left(false, 5.0));
+    }
+
+    #[test]
+    fn clips_loop_or_hold_the_last_frame() {
+        let clip = AnimationClip {
+            frames: vec![4, 5, 6],
+            frame_time: 0.1,
+            looping: true,
+        };
+        let mut animation = PlayerAnimation::default();
+        assert!(!animation.advance(&clip, 0.25));
+        assert_eq!(animation.sheet_index(&clip), 6);
+        assert!(!animation.advance(&clip, 0.1));
+        assert_eq!(animation.sheet_index(&clip), 4);
+
+        let clip = AnimationClip { looping: false, ..clip };
+        let mut animation = PlayerAnimation::default();
+        assert!(!animation.advance(&clip, 0.25));
+        assert!(animation.advance(&clip, 0.1));
+        assert_eq!(animation.sheet_index(&clip), 6);
+
+        animation.set_state(AnimationState::Run);
+        assert_eq!(animation.sheet_index(&clip), 4);
+    }
+
+    #[test]
+    fn clips_need_a_positive_frame_time() {
+        let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), PLAYER_ANIMATIONS);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let mut data: PlayerAnimationData = ron::from_str(&contents).unwrap();
+        for frame_time in [0.0, -0.1, f32::NAN] {
+            data.clips.get_mut(&AnimationState::Run).unwrap().frame_time = frame_time;
+            assert!(data.validate().is_err(), "{} accepted", frame_time);
+        }
+    }
+
+    #[test]
+    fn bundled_animations_are_valid() {
+        let path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), PLAYER_ANIMATIONS);
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let data: PlayerAnimationData = ron::from_str(&contents).unwrap();
+        data.validate().unwrap();
+        let image = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), data.image);
+        assert!(std::path::Path::new(&image).exists(), "{} is missing", image);
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::settings::GameSettings;
+use crate::states::GameState;
+
+/// `GameSettings::air_jumps` value for jumping as often as the player likes.
+pub const UNLIMITED_AIR_JUMPS: u32 = u32::MAX;
+/// Offered mid-air jump counts.
+pub const AIR_JUMP_OPTIONS: [u32; 5] = [0, 1, 2, 3, UNLIMITED_AIR_JUMPS];
+
+/// An assist was on at some point during the current level. Completions and
+/// skips carry it in `LevelCompleted`, so records only count unassisted play.
+#[derive(Resource, Debug, Default)]
+pub struct LevelAssisted(pub bool);
+
+/// Invincibility, mid-air jumps and slowed game speed all count as assists.
+pub fn assists_active(settings: &GameSettings) -> bool {
+    settings.invincible || settings.air_jumps > 0 || settings.game_speed < 1.0
+}
+
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        // Every entry to Playing restarts the level, resuming from pause included
+        app.init_resource::<LevelAssisted>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_level_assisted, track_assists).chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                track_assists.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn reset_level_assisted(mut assisted: ResMut<LevelAssisted>) {
+    assisted.0 = false;
+}
+
+fn track_assists(settings: Res<GameSettings>, mut assisted: ResMut<LevelAssisted>) {
+    if !assisted.0 && assists_active(&settings) {
+        assisted.0 = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    #[test]
+    fn resuming_without_assists_clears_the_flag() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, AssistPlugin));
+        app.init_state::<GameState>();
+        app.insert_resource(GameSettings {
+            invincible: true,
+            ..default()
+        });
+
+        let enter = |app: &mut App, state| {
+            app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+        };
+        enter(&mut app, GameState::Playing);
+        assert!(app.world().resource::<LevelAssisted>().0);
+
+        // Turned off in the pause menu; resuming restarts the level unassisted
+        enter(&mut app, GameState::Paused);
+        app.world_mut().resource_mut::<GameSettings>().invincible = false;
+        enter(&mut app, GameState::Playing);
+        assert!(!app.world().resource::<LevelAssisted>().0);
+
+        // Turned on while paused counts straight away
+        enter(&mut app, GameState::Paused);
+        app.world_mut().resource_mut::<GameSettings>().air_jumps = 1;
+        enter(&mut app, GameState::Playing);
+        assert!(app.world().resource::<LevelAssisted>().0);
+    }
+
+    #[test]
+    fn every_assist_counts() {
+        assert!(!assists_active(&GameSettings::default()));
+        for settings in [
+            GameSettings {
+                invincible: true,
+                ..default()
+            },
+            GameSettings {
+                air_jumps: 1,
+                ..default()
+            },
+            GameSettings {
+                game_speed: 0.8,
+                ..default()
+            },
+        ] {
+            assert!(assists_active(&settings));
+        }
+        // Showing Skip Level in the pause menu isn't an assist until it is used
+        assert!(!assists_active(&GameSettings {
+            level_skip: true,
+            ..default()
+        }));
+    }
+}
@@ -0,0 +1,487 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::audio::{AddAudioSource, AudioSinkPlayback, Decodable, Pitch, Source, Volume};
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::actions::{Action, Actions};
+use crate::level::{LevelCompleted, LevelMusic};
+use crate::player::{PlayerDied, PlayerJumped};
+use crate::settings::GameSettings;
+use crate::states::GameState;
+
+/// Seconds for one track to fade out while the next fades in.
+const CROSSFADE_SECS: f32 = 1.5;
+/// Samples per second of generated tunes.
+const TUNE_SAMPLE_RATE: u32 = 44_100;
+const MELODY_GAIN: f32 = 0.25;
+const BASS_GAIN: f32 = 0.2;
+
+/// Sound effect requests. Anything can write these; `play_sfx` turns them into sounds.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sfx {
+    Jump,
+    Death,
+    Exit,
+    MenuMove,
+    MenuSelect,
+}
+
+/// Generated tones for each effect, so the game has sound without bundled samples.
+#[derive(Resource)]
+struct SfxAssets {
+    jump: Handle<Pitch>,
+    death: Handle<Pitch>,
+    exit: Handle<Pitch>,
+    menu_move: Handle<Pitch>,
+    menu_select: Handle<Pitch>,
+}
+
+impl SfxAssets {
+    fn get(&self, sfx: Sfx) -> Handle<Pitch> {
+        match sfx {
+            Sfx::Jump => self.jump.clone(),
+            Sfx::Death => self.death.clone(),
+            Sfx::Exit => self.exit.clone(),
+            Sfx::MenuMove => self.menu_move.clone(),
+            Sfx::MenuSelect => self.menu_select.clone(),
+        }
+    }
+}
+
+/// A looping background track. `gain` is the crossfade level, multiplied by `music_volume`.
+#[derive(Component)]
+struct MusicTrack {
+    path: String,
+    gain: f32,
+    fading_in: bool,
+}
+
+/// A generated music loop, read from a `.ron` file under `assets/audio/music/`, so the
+/// levels have music without bundled recordings.
+#[derive(Asset, TypePath, Deserialize, Debug, Clone, PartialEq)]
+pub struct Tune {
+    /// Seconds per melody note.
+    pub step: f32,
+    /// MIDI note numbers, 0 for a rest.
+    pub melody: Vec<u8>,
+    /// Notes under the melody, each held for an equal share of the loop.
+    #[serde(default)]
+    pub bass: Vec<u8>,
+}
+
+impl Decodable for Tune {
+    type DecoderItem = f32;
+    type Decoder = TuneDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        let step_samples = ((self.step * TUNE_SAMPLE_RATE as f32) as usize).max(1);
+        TuneDecoder {
+            tune: self.clone(),
+            step_samples,
+            total: step_samples * self.melody.len(),
+            sample: 0,
+        }
+    }
+}
+
+/// Plays a `Tune` once through; looping is left to the playback settings.
+pub struct TuneDecoder {
+    tune: Tune,
+    step_samples: usize,
+    total: usize,
+    sample: usize,
+}
+
+impl Iterator for TuneDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.sample >= self.total {
+            return None;
+        }
+        let seconds = self.sample as f32 / TUNE_SAMPLE_RATE as f32;
+        // Position in melody steps
+        let position = self.sample as f32 / self.step_samples as f32;
+        let step = position as usize;
+        let mut value = voice(self.tune.melody[step], seconds, position.fract()) * MELODY_GAIN;
+        if !self.tune.bass.is_empty() {
+            let bass_steps = self.tune.melody.len() as f32 / self.tune.bass.len() as f32;
+            let bass = ((position / bass_steps) as usize).min(self.tune.bass.len() - 1);
+            value += voice(self.tune.bass[bass], seconds, (position % bass_steps) / bass_steps) * BASS_GAIN;
+        }
+        self.sample += 1;
+        Some(value)
+    }
+}
+
+impl Source for TuneDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.total - self.sample)
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        TUNE_SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(self.total as f64 / TUNE_SAMPLE_RATE as f64))
+    }
+}
+
+/// A triangle wave for `note`, `into` of the way through it: a short attack, then fading out.
+fn voice(note: u8, seconds: f32, into: f32) -> f32 {
+    if note == 0 {
+        return 0.0;
+    }
+    let hz = 440.0 * 2f32.powf((note as f32 - 69.0) / 12.0);
+    let phase = (hz * seconds).fract();
+    let wave = 4.0 * (phase - 0.5).abs() - 1.0;
+    let envelope = (into / 0.05).min(1.0) * (1.0 - into);
+    wave * envelope
+}
+
+fn load_tune(path: &Path) -> Option<Tune> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Failed to read tune {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    let tune: Tune = match ron::from_str(&contents) {
+        Ok(tune) => tune,
+        Err(e) => {
+            warn!("Failed to parse tune {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    if tune.step <= 0.0 || tune.melody.is_empty() {
+        warn!("Tune {} needs a positive step and at least one note", path.display());
+        return None;
+    }
+    Some(tune)
+}
+
+/// Directory the AssetServer reads from, to tell whether a level's track is there.
+#[derive(Resource, Debug)]
+struct AssetRoot(PathBuf);
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        let file_path = app
+            .get_added_plugins::<AssetPlugin>()
+            .first()
+            .map(|plugin| plugin.file_path.clone())
+            .unwrap_or_else(|| AssetPlugin::default().file_path);
+        app.insert_resource(AssetRoot(FileAssetReader::new(file_path).root_path().clone()))
+            .add_audio_source::<Tune>()
+            .add_message::<Sfx>()
+            .add_systems(Startup, create_sfx_assets)
+            .add_systems(
+                Update,
+                (
+                    sfx_from_gameplay,
+                    sfx_from_menus.run_if(not(in_state(GameState::Playing))),
+                    play_sfx,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, (sync_music, crossfade_music).chain());
+    }
+}
+
+fn create_sfx_assets(mut commands: Commands, mut pitches: ResMut<Assets<Pitch>>) {
+    let mut tone = |hz: f32, ms: u64| pitches.add(Pitch::new(hz, Duration::from_millis(ms)));
+    commands.insert_resource(SfxAssets {
+        jump: tone(660.0, 80),
+        death: tone(150.0, 300),
+        exit: tone(880.0, 400),
+        menu_move: tone(440.0, 40),
+        menu_select: tone(550.0, 80),
+    });
+}
+
+fn sfx_from_gameplay(
+    mut jumped: MessageReader<PlayerJumped>,
+    mut died: MessageReader<PlayerDied>,
+    mut completed: MessageReader<LevelCompleted>,
+    mut sfx: MessageWriter<Sfx>,
+) {
+    for _ in jumped.read() {
+        sfx.write(Sfx::Jump);
+    }
+    for _ in died.read() {
+        sfx.write(Sfx::Death);
+    }
+    for _ in completed.read() {
+        sfx.write(Sfx::Exit);
+    }
+}
+
+/// Every menu screen navigates with the menu actions and with mouse hover + click.
+fn sfx_from_menus(
+    actions: Actions,
+    buttons: Query<&Interaction, (Changed<Interaction>, With<Button>)>,
+    mut sfx: MessageWriter<Sfx>,
+) {
+    let arrows = [Action::MenuUp, Action::MenuDown, Action::MenuLeft, Action::MenuRight];
+    let hovered = buttons.iter().any(|i| *i == Interaction::Hovered);
+    let clicked = buttons.iter().any(|i| *i == Interaction::Pressed);
+
+    if actions.just_pressed(Action::Confirm) || clicked {
+        sfx.write(Sfx::MenuSelect);
+    } else if arrows.into_iter().any(|a| actions.just_pressed(a)) || hovered {
+        sfx.write(Sfx::MenuMove);
+    }
+}
+
+fn play_sfx(
+    mut commands: Commands,
+    mut requests: MessageReader<Sfx>,
+    assets: Res<SfxAssets>,
+    settings: Res<GameSettings>,
+) {
+    for sfx in requests.read() {
+        if settings.sfx_volume <= 0.0 {
+            continue;
+        }
+        commands.spawn((
+            AudioPlayer(assets.get(*sfx)),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(settings.sfx_volume)),
+        ));
+    }
+}
+
+/// Level music plays during the level and its pause menu; the other screens are silent.
+fn desired_track(state: &GameState, level_music: &LevelMusic) -> Option<String> {
+    match state {
+        GameState::Playing | GameState::Paused | GameState::LevelTransition => level_music.0.clone(),
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn sync_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut tunes: ResMut<Assets<Tune>>,
+    asset_root: Res<AssetRoot>,
+    state: Res<State<GameState>>,
+    level_music: Res<LevelMusic>,
+    mut tracks: Query<&mut MusicTrack>,
+    mut last_desired: Local<Option<String>>,
+) {
+    let desired = desired_track(state.get(), &level_music);
+
+    // Everything but the wanted track fades out; a track fading out can come back
+    let mut has_track = false;
+    for mut track in &mut tracks {
+        track.fading_in = Some(&track.path) == desired.as_ref();
+        has_track |= track.fading_in;
+    }
+    if has_track || *last_desired == desired {
+        return;
+    }
+    *last_desired = desired.clone();
+
+    let Some(path) = desired else {
+        return;
+    };
+    let full_path = asset_root.0.join(&path);
+    if !full_path.is_file() {
+        info!("Music track {} not found, playing without music", path);
+        return;
+    }
+    // `.ron` tracks are tunes to generate, anything else an audio file
+    let tune = if path.ends_with(".ron") {
+        let Some(tune) = load_tune(&full_path) else {
+            return;
+        };
+        Some(tunes.add(tune))
+    } else {
+        None
+    };
+    let mut track = commands.spawn((
+        MusicTrack {
+            path: path.clone(),
+            gain: 0.0,
+            fading_in: true,
+        },
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+    ));
+    match tune {
+        Some(tune) => track.insert(AudioPlayer(tune)),
+        None => track.insert(AudioPlayer::new(asset_server.load(path))),
+    };
+}
+
+fn step_fade(gain: f32, fading_in: bool, dt: f32) -> f32 {
+    let step = dt / CROSSFADE_SECS;
+    if fading_in {
+        (gain + step).min(1.0)
+    } else {
+        (gain - step).max(0.0)
+    }
+}
+
+/// Advances fades and applies `music_volume` every frame, so the slider works live.
+/// Runs on real time, so game speed doesn't stretch the fades.
+fn crossfade_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<GameSettings>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+) {
+    for (entity, mut track, sink) in &mut tracks {
+        track.gain = step_fade(track.gain, track.fading_in, time.delta_secs());
+        if !track.fading_in && track.gain == 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(settings.music_volume * track.gain));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::KeyBindings;
+    use bevy::time::TimeUpdateStrategy;
+
+    #[derive(Resource, Default)]
+    struct Heard(Vec<Sfx>);
+
+    fn collect_sfx(mut requests: MessageReader<Sfx>, mut heard: ResMut<Heard>) {
+        heard.0.extend(requests.read().copied());
+    }
+
+    fn sfx_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<Sfx>();
+        app.add_message::<PlayerJumped>();
+        app.add_message::<PlayerDied>();
+        app.add_message::<LevelCompleted>();
+        app.init_resource::<Heard>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app
+    }
+
+    #[test]
+    fn gameplay_events_queue_sfx() {
+        let mut app = sfx_app();
+        app.add_systems(Update, (sfx_from_gameplay, collect_sfx).chain());
+
+        app.world_mut().write_message(PlayerJumped);
+        app.world_mut().write_message(PlayerDied::default());
+        app.world_mut().write_message(LevelCompleted { index: 0, assisted: false });
+        app.update();
+
+        assert_eq!(app.world().resource::<Heard>().0, vec![Sfx::Jump, Sfx::Death, Sfx::Exit]);
+    }
+
+    #[test]
+    fn menu_keys_queue_sfx() {
+        let mut app = sfx_app();
+        app.add_systems(Update, (sfx_from_menus, collect_sfx).chain());
+
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::ArrowDown);
+        app.update();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().clear();
+        app.world_mut().resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Enter);
+        app.update();
+
+        assert_eq!(app.world().resource::<Heard>().0, vec![Sfx::MenuMove, Sfx::MenuSelect]);
+    }
+
+    #[test]
+    fn fade_is_clamped() {
+        assert_eq!(step_fade(0.0, true, CROSSFADE_SECS / 2.0), 0.5);
+        assert_eq!(step_fade(0.9, true, CROSSFADE_SECS), 1.0);
+        assert_eq!(step_fade(0.1, false, CROSSFADE_SECS), 0.0);
+    }
+
+    #[test]
+    fn music_fades_out_on_menus() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::asset::AssetPlugin::default(),
+            bevy::state::app::StatesPlugin,
+        ));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.insert_state(GameState::Menu);
+        app.init_asset::<Tune>();
+        app.init_resource::<GameSettings>();
+        app.insert_resource(AssetRoot(PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/assets"))));
+        app.insert_resource(LevelMusic(Some("audio/music/level_01.ron".to_string())));
+        app.add_systems(Update, (sync_music, crossfade_music).chain());
+
+        let track = app
+            .world_mut()
+            .spawn(MusicTrack {
+                path: "audio/music/level_01.ron".to_string(),
+                gain: 1.0,
+                fading_in: true,
+            })
+            .id();
+
+        app.update();
+        app.update();
+        let gain = app.world().entity(track).get::<MusicTrack>().unwrap().gain;
+        assert!(gain < 1.0 && gain > 0.0);
+
+        for _ in 0..20 {
+            app.update();
+        }
+        assert!(app.world().get_entity(track).is_err());
+    }
+
+    #[test]
+    fn tune_plays_each_note_for_a_step() {
+        let tune = Tune {
+            step: 0.1,
+            melody: vec![69, 0, 81],
+            bass: vec![45],
+        };
+        let decoder = tune.decoder();
+        assert_eq!(decoder.total_duration(), Some(Duration::from_millis(300)));
+
+        let samples: Vec<f32> = decoder.collect();
+        let step = samples.len() / 3;
+        assert_eq!(samples.len(), step * 3);
+        assert!(samples.iter().all(|s| s.abs() <= MELODY_GAIN + BASS_GAIN));
+        // The rest leaves only the bass
+        let loudest = |range: &[f32]| range.iter().fold(0.0f32, |max, s| max.max(s.abs()));
+        assert!(loudest(&samples[step..2 * step]) <= BASS_GAIN);
+        assert!(loudest(&samples[..step]) > BASS_GAIN);
+    }
+
+    #[test]
+    fn bundled_tunes_are_valid() {
+        let dir = format!("{}/assets/audio/music", env!("CARGO_MANIFEST_DIR"));
+        let mut count = 0;
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "ron") {
+                continue;
+            }
+            let tune = load_tune(&path).unwrap_or_else(|| panic!("{} is not a valid tune", path.display()));
+            assert!(tune.melody.iter().chain(&tune.bass).all(|note| *note <= 127));
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+}
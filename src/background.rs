@@ -0,0 +1,240 @@
+use bevy::asset::RenderAssetUsages;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::transform::TransformSystems;
+use serde::Deserialize;
+
+use crate::states::GameState;
+
+/// Widest and tallest view the background has to cover (4K at UI scale 1).
+const MAX_VIEW_SIZE: Vec2 = Vec2::new(3840.0, 2160.0);
+/// Far behind the level tiles; each layer is drawn one step in front of the one before it.
+const BACKGROUND_Z: f32 = -100.0;
+/// Rows in the generated gradient texture; it's stretched to the view height.
+const GRADIENT_STEPS: u32 = 64;
+
+/// One parallax layer behind a level. Layers are listed back to front.
+#[derive(Deserialize, Debug, Clone)]
+pub struct BackgroundLayer {
+    pub fill: LayerFill,
+    /// How far the layer moves with the camera: 0 stays put on screen, 1 moves with the level.
+    pub scroll: f32,
+    /// Repeat the layer left and right so it never runs out.
+    #[serde(default)]
+    pub repeat_x: bool,
+    /// Position at the level origin, in pixels.
+    #[serde(default)]
+    pub offset: (f32, f32),
+}
+
+impl BackgroundLayer {
+    /// An image needs a positive size to be drawn, and to be repeated without end.
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.fill {
+            LayerFill::Image { path, size } if !(size.0 > 0.0 && size.1 > 0.0) => {
+                Err(format!("background '{}' has size {:?}, it must be positive", path, size))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub enum LayerFill {
+    /// Image under `assets/`, stretched to `size` in pixels.
+    Image { path: String, size: (f32, f32) },
+    /// Vertical gradient in sRGB covering the whole view. It looks the same all the way
+    /// across, so it always follows the camera horizontally.
+    Gradient { top: (f32, f32, f32), bottom: (f32, f32, f32) },
+}
+
+/// One sprite of a background layer; repeated layers have one per copy.
+#[derive(Component, Debug, Clone, Copy)]
+struct ParallaxSprite {
+    scroll: Vec2,
+    offset: Vec2,
+    /// Width of one repeat, or `None` for a layer drawn once.
+    repeat_width: Option<f32>,
+    /// Which repeat this sprite is, counted from the one nearest the camera.
+    copy: i32,
+}
+
+/// Where a layer's origin is drawn for a camera at `camera`.
+pub fn layer_origin(camera: Vec2, scroll: Vec2, offset: Vec2) -> Vec2 {
+    camera * (Vec2::ONE - scroll) + offset
+}
+
+/// The repeat of a layer at `origin_x` nearest to `camera_x`.
+pub fn nearest_repeat(camera_x: f32, origin_x: f32, width: f32) -> f32 {
+    origin_x + ((camera_x - origin_x) / width).round() * width
+}
+
+/// Copies on each side of the middle one that cover the widest view.
+fn repeats_per_side(width: f32) -> i32 {
+    (MAX_VIEW_SIZE.x / 2.0 / width).ceil() as i32
+}
+
+fn gradient_image(top: (f32, f32, f32), bottom: (f32, f32, f32)) -> Image {
+    let top = Srgba::rgb(top.0, top.1, top.2);
+    let bottom = Srgba::rgb(bottom.0, bottom.1, bottom.2);
+    let data = (0..GRADIENT_STEPS)
+        .flat_map(|row| {
+            let t = row as f32 / (GRADIENT_STEPS - 1) as f32;
+            top.mix(&bottom, t).to_u8_array()
+        })
+        .collect();
+    Image::new(
+        Extent3d {
+            width: 1,
+            height: GRADIENT_STEPS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Spawns level backgrounds. They're sprites without colliders, so physics never sees them.
+#[derive(SystemParam)]
+pub struct Backgrounds<'w> {
+    asset_server: Res<'w, AssetServer>,
+    images: ResMut<'w, Assets<Image>>,
+}
+
+impl Backgrounds<'_> {
+    pub fn spawn(&mut self, commands: &mut Commands, layers: &[BackgroundLayer]) {
+        for (index, layer) in layers.iter().enumerate() {
+            let z = BACKGROUND_Z + index as f32;
+            let offset = Vec2::new(layer.offset.0, layer.offset.1);
+            let (image, size, scroll, repeat_width) = match &layer.fill {
+                LayerFill::Image { path, size } => {
+                    let size = Vec2::new(size.0, size.1);
+                    let repeat_width = layer.repeat_x.then_some(size.x);
+                    (self.asset_server.load(path), size, Vec2::splat(layer.scroll), repeat_width)
+                }
+                LayerFill::Gradient { top, bottom } => (
+                    self.images.add(gradient_image(*top, *bottom)),
+                    MAX_VIEW_SIZE,
+                    Vec2::new(0.0, layer.scroll),
+                    None,
+                ),
+            };
+
+            let copies = match repeat_width {
+                Some(width) => repeats_per_side(width),
+                None => 0,
+            };
+            for copy in -copies..=copies {
+                commands.spawn((
+                    ParallaxSprite {
+                        scroll,
+                        offset,
+                        repeat_width,
+                        copy,
+                    },
+                    Sprite {
+                        image: image.clone(),
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    Transform::from_xyz(offset.x, offset.y, z),
+                    DespawnOnExit::<GameState>(GameState::Playing),
+                ));
+            }
+        }
+    }
+}
+
+pub struct BackgroundPlugin;
+
+impl Plugin for BackgroundPlugin {
+    fn build(&self, app: &mut App) {
+        // After the camera has followed, snapped and shaken for this frame
+        app.add_systems(
+            PostUpdate,
+            scroll_background
+                .before(TransformSystems::Propagate)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+fn scroll_background(
+    camera_query: Query<&Transform, With<Camera2d>>,
+    mut sprites: Query<(&ParallaxSprite, &mut Transform), Without<Camera2d>>,
+) {
+    let Ok(camera) = camera_query.single() else {
+        return;
+    };
+    let camera = camera.translation.truncate();
+    for (sprite, mut transform) in &mut sprites {
+        let mut position = layer_origin(camera, sprite.scroll, sprite.offset);
+        if let Some(width) = sprite.repeat_width {
+            position.x = nearest_repeat(camera.x, position.x, width) + sprite.copy as f32 * width;
+        }
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scroll_factor_sets_apparent_speed() {
+        let offset = Vec2::new(10.0, -50.0);
+        let camera = Vec2::new(200.0, 40.0);
+        for scroll in [0.0, 0.3, 1.0] {
+            let scroll = Vec2::splat(scroll);
+            // On screen the layer moves against the camera by `scroll` times its movement
+            let before = layer_origin(Vec2::ZERO, scroll, offset);
+            let after = layer_origin(camera, scroll, offset) - camera;
+            assert!((after - before + camera * scroll).length() < 1e-3);
+        }
+        assert_eq!(layer_origin(camera, Vec2::ZERO, offset), camera + offset);
+        assert_eq!(layer_origin(camera, Vec2::ONE, offset), offset);
+    }
+
+    #[test]
+    fn repeats_stay_around_the_camera() {
+        let width = 512.0;
+        for camera_x in [-3000.0, -100.0, 0.0, 255.0, 257.0, 9000.0] {
+            let middle = nearest_repeat(camera_x, 40.0, width);
+            assert!((middle - camera_x).abs() <= width / 2.0, "{} -> {}", camera_x, middle);
+            assert!(((middle - 40.0) / width).fract().abs() < 1e-3);
+        }
+        // Even with the middle copy half a width off, the copies cover the widest view
+        let copies = (2 * repeats_per_side(width) + 1) as f32;
+        assert!(copies * width >= MAX_VIEW_SIZE.x + width);
+    }
+
+    #[test]
+    fn image_layers_need_a_positive_size() {
+        let layer = |size| BackgroundLayer {
+            fill: LayerFill::Image {
+                path: "backgrounds/hills.png".to_string(),
+                size,
+            },
+            scroll: 0.5,
+            repeat_x: true,
+            offset: (0.0, 0.0),
+        };
+        assert!(layer((512.0, 256.0)).validate().is_ok());
+        assert!(layer((0.0, 256.0)).validate().is_err());
+        assert!(layer((512.0, -1.0)).validate().is_err());
+        assert!(layer((f32::NAN, 256.0)).validate().is_err());
+    }
+
+    #[test]
+    fn gradient_runs_top_to_bottom() {
+        let image = gradient_image((1.0, 0.0, 0.0), (0.0, 0.0, 1.0));
+        let data = image.data.unwrap();
+        assert_eq!(data.len(), (GRADIENT_STEPS * 4) as usize);
+        assert_eq!(&data[..4], &[255, 0, 0, 255]);
+        assert_eq!(&data[data.len() - 4..], &[0, 0, 255, 255]);
+    }
+}
@@ -1,18 +1,62 @@
 use bevy::prelude::*;
 
-use crate::player::Player;
+use crate::fade::{spawn_screen_fade, ScreenFade};
+use crate::player::{Player, PlayerDied};
+use crate::settings::GameSettings;
 use crate::states::GameState;
 
 const CAMERA_SPEED: f32 = 5.0;
+const FADE_DURATION: f32 = 0.25;
+const SHAKE_FREQUENCY: f32 = 40.0;
+const DEATH_SHAKE_INTENSITY: f32 = 8.0;
+const DEATH_SHAKE_DURATION: f32 = 0.3;
+
+/// Teleports the camera onto the player instead of lerping there.
+#[derive(Message, Debug, Clone, Copy, Default)]
+pub struct SnapCamera {
+    /// Cover the jump with a short fade from black.
+    pub fade: bool,
+}
+
+/// Shakes the camera. Any system can write this message.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct ShakeCamera {
+    /// Maximum offset in pixels.
+    pub intensity: f32,
+    /// Duration in seconds; intensity decays linearly to zero.
+    pub duration: f32,
+}
+
+/// Current shake state and the offset applied to the camera last frame.
+#[derive(Resource, Default)]
+struct CameraShake {
+    intensity: f32,
+    duration: f32,
+    remaining: f32,
+    offset: Vec2,
+}
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera)
+        app.add_message::<SnapCamera>()
+            .add_message::<ShakeCamera>()
+            .init_resource::<CameraShake>()
+            .add_systems(Startup, spawn_camera)
+            .add_systems(OnEnter(GameState::Playing), snap_on_enter)
+            .add_systems(OnExit(GameState::Playing), clear_shake_offset)
             .add_systems(
                 Update,
-                camera_follow.run_if(in_state(GameState::Playing)),
+                (
+                    react_to_death,
+                    clear_shake_offset,
+                    camera_snap,
+                    camera_follow,
+                    camera_shake,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
@@ -21,10 +65,82 @@ fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2d);
 }
 
+fn snap_on_enter(mut snap: MessageWriter<SnapCamera>) {
+    snap.write(SnapCamera { fade: true });
+}
+
+fn react_to_death(
+    mut deaths: MessageReader<PlayerDied>,
+    mut snap: MessageWriter<SnapCamera>,
+    mut shake: MessageWriter<ShakeCamera>,
+) {
+    if deaths.read().count() == 0 {
+        return;
+    }
+    snap.write(SnapCamera { fade: true });
+    shake.write(ShakeCamera {
+        intensity: DEATH_SHAKE_INTENSITY,
+        duration: DEATH_SHAKE_DURATION,
+    });
+}
+
+/// Removes last frame's shake offset so follow and snap work on the real position.
+fn clear_shake_offset(
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if shake.offset == Vec2::ZERO {
+        return;
+    }
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        camera_transform.translation -= shake.offset.extend(0.0);
+    }
+    shake.offset = Vec2::ZERO;
+}
+
+fn camera_snap(
+    mut commands: Commands,
+    mut snaps: MessageReader<SnapCamera>,
+    player_query: Query<&Transform, With<Player>>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
+    fades: Query<(Entity, &ScreenFade)>,
+) {
+    let mut fade = false;
+    let mut requested = false;
+    for snap in snaps.read() {
+        requested = true;
+        fade |= snap.fade;
+    }
+    if !requested {
+        return;
+    }
+
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let Ok(mut camera_transform) = camera_query.single_mut() else {
+        return;
+    };
+    camera_transform.translation.x = player_transform.translation.x;
+    camera_transform.translation.y = player_transform.translation.y;
+
+    if fade {
+        // A fade to black is on its way out of the level; leave it running
+        for (entity, existing) in &fades {
+            if !existing.is_fading_out() {
+                commands.entity(entity).despawn();
+            }
+        }
+        spawn_screen_fade(&mut commands, ScreenFade::from_black(FADE_DURATION), GameState::Playing);
+    }
+}
+
+/// Eases toward the player, or stays locked on it with reduced motion.
 fn camera_follow(
     player_query: Query<&Transform, With<Player>>,
     mut camera_query: Query<&mut Transform, (With<Camera2d>, Without<Player>)>,
     time: Res<Time>,
+    settings: Res<GameSettings>,
 ) {
     let Ok(player_transform) = player_query.single() else {
         return;
@@ -33,6 +149,12 @@ fn camera_follow(
         return;
     };
 
+    if settings.reduced_motion {
+        camera_transform.translation.x = player_transform.translation.x;
+        camera_transform.translation.y = player_transform.translation.y;
+        return;
+    }
+
     let player_x = player_transform.translation.x;
     let player_y = player_transform.translation.y;
     let camera_x = camera_transform.translation.x;
@@ -41,3 +163,133 @@ fn camera_follow(
     camera_transform.translation.x = camera_x + (player_x - camera_x) * CAMERA_SPEED * time.delta_secs();
     camera_transform.translation.y = camera_y + (player_y - camera_y) * CAMERA_SPEED * time.delta_secs();
 }
+
+fn camera_shake(
+    mut requests: MessageReader<ShakeCamera>,
+    mut shake: ResMut<CameraShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+    time: Res<Time>,
+    settings: Res<GameSettings>,
+) {
+    if settings.reduced_motion {
+        requests.clear();
+        return;
+    }
+    for request in requests.read() {
+        // A stronger request replaces a weaker one still in progress
+        if request.intensity >= shake.intensity * shake.remaining / shake.duration.max(f32::EPSILON) {
+            shake.intensity = request.intensity;
+            shake.duration = request.duration;
+            shake.remaining = request.duration;
+        }
+    }
+
+    if shake.remaining <= 0.0 {
+        return;
+    }
+    shake.remaining = (shake.remaining - time.delta_secs()).max(0.0);
+
+    let strength = shake.intensity * shake.remaining / shake.duration.max(f32::EPSILON);
+    let t = time.elapsed_secs() * SHAKE_FREQUENCY;
+    shake.offset = Vec2::new(t.sin(), (t * 1.3).cos()) * strength;
+
+    if let Ok(mut camera_transform) = camera_query.single_mut() {
+        camera_transform.translation += shake.offset.extend(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<SnapCamera>();
+        app.add_message::<ShakeCamera>();
+        app.add_message::<PlayerDied>();
+        app.init_resource::<CameraShake>();
+        app.init_resource::<GameSettings>();
+        app.add_systems(
+            Update,
+            (react_to_death, clear_shake_offset, camera_snap, camera_shake).chain(),
+        );
+        app
+    }
+
+    #[test]
+    fn snap_moves_camera_onto_player() {
+        let mut app = test_app();
+        app.world_mut().spawn((Player, Transform::from_xyz(500.0, 300.0, 0.0)));
+        let camera = app.world_mut().spawn((Camera2d, Transform::default())).id();
+
+        app.world_mut().write_message(SnapCamera { fade: false });
+        app.update();
+
+        let transform = app.world().entity(camera).get::<Transform>().unwrap();
+        assert_eq!(transform.translation.x, 500.0);
+        assert_eq!(transform.translation.y, 300.0);
+    }
+
+    #[test]
+    fn death_snaps_camera_and_starts_shake() {
+        let mut app = test_app();
+        app.world_mut().spawn((Player, Transform::from_xyz(-200.0, 50.0, 0.0)));
+        let camera = app.world_mut().spawn((Camera2d, Transform::default())).id();
+
+        app.world_mut().write_message(PlayerDied::default());
+        app.update();
+
+        let shake = app.world().resource::<CameraShake>();
+        assert!(shake.remaining > 0.0);
+
+        let transform = app.world().entity(camera).get::<Transform>().unwrap();
+        let unshaken = transform.translation.truncate() - shake.offset;
+        assert_eq!(unshaken, Vec2::new(-200.0, 50.0));
+    }
+
+    #[test]
+    fn reduced_motion_skips_shake() {
+        let mut app = test_app();
+        app.world_mut().resource_mut::<GameSettings>().reduced_motion = true;
+        app.world_mut().spawn((Player, Transform::from_xyz(-200.0, 50.0, 0.0)));
+        let camera = app.world_mut().spawn((Camera2d, Transform::default())).id();
+
+        app.world_mut().write_message(PlayerDied::default());
+        app.update();
+
+        assert_eq!(app.world().resource::<CameraShake>().remaining, 0.0);
+        let transform = app.world().entity(camera).get::<Transform>().unwrap();
+        assert_eq!(transform.translation.truncate(), Vec2::new(-200.0, 50.0));
+    }
+
+    #[test]
+    fn resuming_snaps_camera_onto_respawned_player() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin, CameraPlugin));
+        app.init_state::<GameState>();
+        app.add_message::<PlayerDied>();
+        app.init_resource::<GameSettings>();
+        // Stands in for the level, which is rebuilt on every entry to Playing
+        app.add_systems(OnEnter(GameState::Playing), |mut commands: Commands| {
+            commands.spawn((
+                Player,
+                Transform::from_xyz(100.0, 50.0, 0.0),
+                DespawnOnExit(GameState::Playing),
+            ));
+        });
+
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app.update();
+        let mut cameras = app.world_mut().query_filtered::<&mut Transform, With<Camera2d>>();
+        // Paused far from the spawn point
+        cameras.single_mut(app.world_mut()).unwrap().translation = Vec3::new(900.0, -300.0, 0.0);
+        for state in [GameState::Paused, GameState::Playing] {
+            app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+        }
+
+        let translation = cameras.single(app.world()).unwrap().translation;
+        assert_eq!(translation.truncate(), Vec2::new(100.0, 50.0));
+    }
+}
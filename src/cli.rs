@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::level::{CurrentLevel, CustomLevel, LevelData, LEVELS};
+use crate::profiles::{launch_profile, ProfileData};
+use crate::states::GameState;
+
+pub const USAGE: &str = "\
+Usage: simple_platformer [OPTIONS]
+
+Options:
+  --level <N|PATH>    Start on level N (1-based) or a level file, skipping the menu
+  --profile <NAME>    Use this profile (created if missing), skipping the picker
+  --data-dir <PATH>   Keep saves, settings and logs in PATH
+  --windowed          Start in a window
+  --fullscreen        Start fullscreen
+  --log <FILTER>      Log filter, e.g. \"simple_platformer=debug\" (overrides RUST_LOG)
+  -h, --help          Print this help";
+
+/// Level requested with `--level`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LevelArg {
+    /// 0-based index into `LEVELS`.
+    Index(usize),
+    /// A level file that isn't one of `LEVELS`.
+    Path(PathBuf),
+}
+
+/// Parsed command line. Everything is optional; `Default` means a normal launch.
+#[derive(Resource, Debug, Clone, PartialEq, Eq, Default)]
+pub struct CliArgs {
+    pub level: Option<LevelArg>,
+    pub profile: Option<String>,
+    pub data_dir: Option<PathBuf>,
+    /// `Some(true)` for `--fullscreen`, `Some(false)` for `--windowed`.
+    pub fullscreen: Option<bool>,
+    pub log_filter: Option<String>,
+    pub help: bool,
+}
+
+pub struct CliPlugin;
+
+impl Plugin for CliPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CliArgs>()
+            .add_systems(Startup, apply_launch_args);
+    }
+}
+
+/// Parses arguments without the program name, e.g. `std::env::args().skip(1)`.
+/// Accepts both `--flag value` and `--flag=value`.
+pub fn parse_args<I, S>(args: I) -> Result<CliArgs, String>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter().map(Into::into);
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag.to_string(), Some(value.to_string())),
+            _ => (arg, None),
+        };
+        let mut value = |name: &str| {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match flag.as_str() {
+            "--level" => parsed.level = Some(parse_level(&value("--level")?)?),
+            "--profile" => parsed.profile = Some(value("--profile")?),
+            "--data-dir" => parsed.data_dir = Some(PathBuf::from(value("--data-dir")?)),
+            "--log" => parsed.log_filter = Some(value("--log")?),
+            "--windowed" | "--fullscreen" => {
+                let fullscreen = flag == "--fullscreen";
+                if parsed.fullscreen.is_some_and(|f| f != fullscreen) {
+                    return Err("--windowed and --fullscreen can't be combined".to_string());
+                }
+                parsed.fullscreen = Some(fullscreen);
+            }
+            "-h" | "--help" => parsed.help = true,
+            other => return Err(format!("Unknown argument '{}'", other)),
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// A number is a 1-based level index; anything else is a path. Paths to bundled
+/// levels map to their index so progress and records still apply.
+fn parse_level(value: &str) -> Result<LevelArg, String> {
+    if let Ok(number) = value.parse::<usize>() {
+        if (1..=LEVELS.len()).contains(&number) {
+            return Ok(LevelArg::Index(number - 1));
+        }
+        return Err(format!("Level must be between 1 and {}", LEVELS.len()));
+    }
+
+    let path = Path::new(value);
+    if !path.is_file() {
+        return Err(format!("Level file '{}' not found", value));
+    }
+    // Checked here so a broken file is a usage error rather than a panic on load
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("Can't read level file '{}': {}", value, e))?;
+    ron::from_str::<LevelData>(&contents)
+        .map_err(|e| e.to_string())
+        .and_then(|level| level.validate())
+        .map_err(|e| format!("Level file '{}' is invalid: {}", value, e))?;
+    let same_file = |bundled: &str| match (std::fs::canonicalize(bundled), std::fs::canonicalize(path)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => Path::new(bundled) == path,
+    };
+    Ok(match LEVELS.iter().position(|bundled| same_file(bundled)) {
+        Some(index) => LevelArg::Index(index),
+        None => LevelArg::Path(path.to_path_buf()),
+    })
+}
+
+/// Skips the profile picker and/or the menu when `--profile` or `--level` was given.
+fn apply_launch_args(
+    args: Res<CliArgs>,
+    mut profile_data: ProfileData,
+    mut current_level: ResMut<CurrentLevel>,
+    mut custom: ResMut<CustomLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if args.level.is_none() && args.profile.is_none() {
+        return;
+    }
+
+    let profile = match launch_profile(args.profile.as_deref()) {
+        Ok(name) => name,
+        Err(e) => {
+            // Leave the picker open so a valid profile can be chosen
+            let reason = profile_data.locale.get(e);
+            error!("Can't use profile {:?}: {}", args.profile.as_deref().unwrap_or_default(), reason);
+            return;
+        }
+    };
+    profile_data.activate(&profile);
+
+    match &args.level {
+        Some(LevelArg::Index(index)) => {
+            current_level.0 = *index;
+            info!("Starting on level {}", index + 1);
+            next_state.set(GameState::Playing);
+        }
+        Some(LevelArg::Path(path)) => {
+            current_level.0 = 0;
+            custom.0 = Some(path.clone());
+            info!("Starting on custom level {}", path.display());
+            next_state.set(GameState::Playing);
+        }
+        None => next_state.set(GameState::Menu),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_arguments_is_a_normal_launch() {
+        assert_eq!(parse_args(Vec::<String>::new()), Ok(CliArgs::default()));
+    }
+
+    #[test]
+    fn all_options() {
+        let args = parse_args([
+            "--level",
+            "3",
+            "--profile=Alice",
+            "--data-dir",
+            "/tmp/sp",
+            "--fullscreen",
+            "--log",
+            "simple_platformer=debug",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            args,
+            CliArgs {
+                level: Some(LevelArg::Index(2)),
+                profile: Some("Alice".to_string()),
+                data_dir: Some(PathBuf::from("/tmp/sp")),
+                fullscreen: Some(true),
+                log_filter: Some("simple_platformer=debug".to_string()),
+                help: false,
+            }
+        );
+    }
+
+    #[test]
+    fn level_paths() {
+        // Bundled files resolve to their index
+        let bundled = parse_args(["--level", LEVELS[1]]).unwrap();
+        assert_eq!(bundled.level, Some(LevelArg::Index(1)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.ron");
+        std::fs::copy(LEVELS[0], &path).unwrap();
+        let custom = parse_args(["--level".to_string(), path.display().to_string()]).unwrap();
+        assert_eq!(custom.level, Some(LevelArg::Path(path.clone())));
+
+        // Not a level
+        std::fs::write(&path, "()").unwrap();
+        assert!(parse_args(["--level".to_string(), path.display().to_string()]).is_err());
+
+        // Parses, but a background without width would repeat without end
+        std::fs::write(
+            &path,
+            r#"(name: "Flat", spawn: (0, 0), exit: (1, 0), tiles: [],
+                background: [(fill: Image(path: "hills.png", size: (0, 64)), scroll: 0.5, repeat_x: true)])"#,
+        )
+        .unwrap();
+        assert!(parse_args(["--level".to_string(), path.display().to_string()]).is_err());
+    }
+
+    #[test]
+    fn invalid_arguments() {
+        assert!(parse_args(["--level", "0"]).is_err());
+        assert!(parse_args(["--level", &(LEVELS.len() + 1).to_string()]).is_err());
+        assert!(parse_args(["--level", "no/such/level.ron"]).is_err());
+        assert!(parse_args(["--profile"]).is_err());
+        assert!(parse_args(["--windowed", "--fullscreen"]).is_err());
+        assert!(parse_args(["--speed", "2"]).is_err());
+    }
+
+    #[test]
+    fn help_flag() {
+        assert!(parse_args(["-h"]).unwrap().help);
+        assert!(parse_args(["--help"]).unwrap().help);
+    }
+}
@@ -0,0 +1,327 @@
+use bevy::prelude::*;
+
+use crate::actions::{gamepad_just_pressed, key_name, keys_label, save_bindings, Action, KeyBindings};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::states::GameState;
+use crate::widgets::{
+    spawn_menu, ItemColors, MenuEntry, MenuEvent, MenuItem, MenuStyle, MenuSystems, COLOR_SELECTED,
+};
+
+/// Action rows, then "Reset to Defaults" and "Back".
+const CONTROLS_ITEMS: usize = Action::ALL.len() + 2;
+const RESET_ROW: usize = Action::ALL.len();
+const BACK_ROW: usize = Action::ALL.len() + 1;
+
+const COLOR_WAITING: Color = Color::srgb(0.7, 0.5, 0.1);
+
+/// The action waiting for a new key, if any.
+#[derive(Resource, Default)]
+struct Rebinding(Option<Action>);
+
+/// Line under the list: what to press, or why a key was refused.
+#[derive(Resource, Default)]
+struct ControlsHint(String);
+
+#[derive(Component, Debug, Clone)]
+struct ControlsRow(usize);
+
+#[derive(Component)]
+struct ControlsHintText;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .init_resource::<ControlsHint>()
+            .add_systems(OnEnter(GameState::Controls), setup_controls)
+            .add_systems(
+                Update,
+                (controls_capture, swallow_rebind_input)
+                    .chain()
+                    .before(MenuSystems::Navigate)
+                    .run_if(in_state(GameState::Controls)),
+            )
+            .add_systems(
+                Update,
+                controls_action
+                    .in_set(MenuSystems::Respond)
+                    .run_if(in_state(GameState::Controls)),
+            )
+            .add_systems(
+                Update,
+                (controls_highlight, controls_update_text)
+                    .after(MenuSystems::Respond)
+                    .before(MenuSystems::Display)
+                    .run_if(in_state(GameState::Controls)),
+            );
+    }
+}
+
+fn row_text(index: usize, bindings: &KeyBindings, locale: &Locale) -> String {
+    match Action::ALL.get(index) {
+        Some(action) => format!("{}: {}", locale.get(action.label_key()), keys_label(bindings.keys(*action))),
+        None if index == RESET_ROW => locale.get("controls.reset").to_string(),
+        None => locale.get("controls.back").to_string(),
+    }
+}
+
+fn setup_controls(
+    mut commands: Commands,
+    mut rebinding: ResMut<Rebinding>,
+    mut hint: ResMut<ControlsHint>,
+    bindings: Res<KeyBindings>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
+    rebinding.0 = None;
+    hint.0.clear();
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DespawnOnExit::<GameState>(GameState::Controls),
+        ))
+        .with_children(|parent| {
+            // Title
+            parent.spawn((
+                Text::new(locale.get("controls.title")),
+                fonts.title(40.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(20.0)),
+                    ..default()
+                },
+            ));
+
+            // Rows
+            let entries = (0..CONTROLS_ITEMS)
+                .map(|i| MenuEntry::button(ControlsRow(i), row_text(i, &bindings, &locale)))
+                .collect();
+            let style = MenuStyle {
+                item_width: 400.0,
+                item_height: 34.0,
+                font_size: 20.0,
+                spacing: 4.0,
+                ..default()
+            };
+            spawn_menu(parent, &fonts, style, entries);
+
+            // Hint
+            parent.spawn((
+                ControlsHintText,
+                Text::new(""),
+                fonts.text(18.0),
+                TextColor(Color::srgb(0.8, 0.7, 0.4)),
+                Node {
+                    margin: UiRect::top(Val::Px(15.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+/// While waiting, the next key pressed becomes the action's binding.
+fn controls_capture(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut bindings: ResMut<KeyBindings>,
+    mut rebinding: ResMut<Rebinding>,
+    mut hint: ResMut<ControlsHint>,
+    locale: Res<Locale>,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+    let Some(&key) = keyboard.get_just_pressed().next() else {
+        // Gamepad buttons aren't rebindable, but can still cancel
+        if gamepad_just_pressed(&gamepads, Action::Back) {
+            hint.0.clear();
+            rebinding.0 = None;
+        }
+        return;
+    };
+
+    if bindings.keys(Action::Back).contains(&key) {
+        hint.0.clear();
+        rebinding.0 = None;
+        return;
+    }
+    if let Some(other) = bindings.conflict(action, key) {
+        hint.0 = locale.format(
+            "controls.conflict",
+            &[("key", &key_name(key)), ("action", &locale.get(other.label_key()))],
+        );
+        return;
+    }
+
+    bindings.set(action, key);
+    save_bindings(&bindings);
+    info!("{:?} bound to {}", action, key_name(key));
+    hint.0.clear();
+    rebinding.0 = None;
+}
+
+/// The key that finishes, cancels or is refused for a rebind must not reach the menu too.
+fn swallow_rebind_input(
+    rebinding: Res<Rebinding>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut gamepads: Query<&mut Gamepad>,
+) {
+    if rebinding.0.is_none() && !rebinding.is_changed() {
+        return;
+    }
+    keyboard.clear();
+    for mut gamepad in &mut gamepads {
+        gamepad.digital_mut().clear();
+    }
+}
+
+fn controls_action(
+    mut events: MessageReader<MenuEvent>,
+    rows: Query<&ControlsRow>,
+    mut rebinding: ResMut<Rebinding>,
+    mut hint: ResMut<ControlsHint>,
+    mut bindings: ResMut<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+    locale: Res<Locale>,
+) {
+    for event in events.read() {
+        let row = match event {
+            MenuEvent::Activated { item, .. } => match rows.get(*item) {
+                Ok(row) => row.0,
+                Err(_) => continue,
+            },
+            MenuEvent::Back { .. } => BACK_ROW,
+            MenuEvent::Changed { .. } => continue,
+        };
+
+        match row {
+            RESET_ROW => {
+                *bindings = KeyBindings::default();
+                save_bindings(&bindings);
+                hint.0 = locale.get("controls.reset_done").to_string();
+            }
+            BACK_ROW => next_state.set(GameState::Settings),
+            row => {
+                let action = Action::ALL[row];
+                rebinding.0 = Some(action);
+                hint.0 = locale.format(
+                    "controls.press_key",
+                    &[
+                        ("action", &locale.get(action.label_key())),
+                        ("back", &keys_label(bindings.keys(Action::Back))),
+                    ],
+                );
+            }
+        }
+    }
+}
+
+/// The row waiting for a key is drawn in its own color while selected.
+fn controls_highlight(rebinding: Res<Rebinding>, mut rows: Query<(&ControlsRow, &mut ItemColors)>) {
+    if !rebinding.is_changed() {
+        return;
+    }
+    for (row, mut colors) in &mut rows {
+        let waiting = rebinding.0.is_some_and(|action| Action::ALL.get(row.0) == Some(&action));
+        colors.selected = if waiting { COLOR_WAITING } else { COLOR_SELECTED };
+    }
+}
+
+fn controls_update_text(
+    bindings: Res<KeyBindings>,
+    hint: Res<ControlsHint>,
+    locale: Res<Locale>,
+    mut rows: Query<(&ControlsRow, &mut MenuItem)>,
+    mut hint_text: Query<&mut Text, With<ControlsHintText>>,
+) {
+    if !bindings.is_changed() && !hint.is_changed() {
+        return;
+    }
+    for (row, mut item) in &mut rows {
+        item.label = row_text(row.0, &bindings, &locale);
+    }
+    for mut text in &mut hint_text {
+        **text = hint.0.clone();
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::widgets::WidgetsPlugin;
+
+    fn controls_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<ControlsHint>();
+        app.init_resource::<Locale>();
+        app.add_systems(Update, controls_capture);
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release_all();
+        keyboard.clear();
+        keyboard.press(key);
+        app.update();
+    }
+
+    #[test]
+    fn conflicting_key_is_refused() {
+        let mut app = controls_app();
+        app.insert_resource(Rebinding(Some(Action::Jump)));
+
+        press(&mut app, KeyCode::KeyA);
+
+        assert_eq!(app.world().resource::<KeyBindings>().keys(Action::Jump), &[KeyCode::Space]);
+        assert_eq!(app.world().resource::<Rebinding>().0, Some(Action::Jump));
+        assert!(app.world().resource::<ControlsHint>().0.contains("Move Left"));
+    }
+
+    #[test]
+    fn back_key_cancels_rebinding() {
+        let mut app = controls_app();
+        app.insert_resource(Rebinding(Some(Action::Jump)));
+
+        press(&mut app, KeyCode::Escape);
+
+        assert_eq!(app.world().resource::<KeyBindings>().keys(Action::Jump), &[KeyCode::Space]);
+        assert_eq!(app.world().resource::<Rebinding>().0, None);
+    }
+
+    #[test]
+    fn cancelling_a_rebind_stays_on_the_screen() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin, WidgetsPlugin, ControlsPlugin));
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<Locale>();
+        app.init_resource::<UiFonts>();
+        app.insert_state(GameState::Controls);
+        app.update();
+
+        // Enter on the first row waits for a key, Escape cancels without leaving
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.world().resource::<Rebinding>().0, Some(Action::ALL[0]));
+        press(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(app.world().resource::<Rebinding>().0, None);
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Controls);
+
+        press(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Settings);
+    }
+}
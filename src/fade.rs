@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+
+use crate::states::GameState;
+
+/// Full-screen black overlay fading in or out. Spawn with `spawn_screen_fade`.
+#[derive(Component, Debug)]
+pub struct ScreenFade {
+    timer: Timer,
+    /// Darkening to black rather than clearing from it.
+    to_black: bool,
+    /// State to switch to once the screen is black.
+    next: Option<GameState>,
+}
+
+impl ScreenFade {
+    /// Starts black and clears over `duration`, then goes away.
+    pub fn from_black(duration: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            to_black: false,
+            next: None,
+        }
+    }
+
+    /// Darkens to black over `duration`, then switches to `next`. Stays black until
+    /// the state it was spawned in is left.
+    pub fn to_black(duration: f32, next: GameState) -> Self {
+        Self {
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            to_black: true,
+            next: Some(next),
+        }
+    }
+
+    pub fn is_fading_out(&self) -> bool {
+        self.to_black
+    }
+
+    /// Opacity of the overlay at this point of the fade.
+    pub fn alpha(&self) -> f32 {
+        let t = self.timer.fraction();
+        if self.to_black { t } else { 1.0 - t }
+    }
+}
+
+/// Spawns `fade` over everything but the pause overlay; it's removed on leaving `state`.
+pub fn spawn_screen_fade(commands: &mut Commands, fade: ScreenFade, state: GameState) {
+    let color = Color::BLACK.with_alpha(fade.alpha());
+    commands.spawn((
+        fade,
+        Node {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        BackgroundColor(color),
+        // Above the HUD, below the pause overlay
+        GlobalZIndex(50),
+        DespawnOnExit::<GameState>(state),
+    ));
+}
+
+/// Run condition: the screen is fading to black, so whatever is under it should hold still.
+pub fn fading_out(fades: Query<&ScreenFade>) -> bool {
+    fades.iter().any(ScreenFade::is_fading_out)
+}
+
+pub struct FadePlugin;
+
+impl Plugin for FadePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_screen_fades);
+    }
+}
+
+fn update_screen_fades(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut fades: Query<(Entity, &mut ScreenFade, &mut BackgroundColor)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (entity, mut fade, mut bg) in &mut fades {
+        fade.timer.tick(time.delta());
+        *bg = BackgroundColor(Color::BLACK.with_alpha(fade.alpha()));
+        if !fade.timer.is_finished() {
+            continue;
+        }
+        if !fade.to_black {
+            commands.entity(entity).despawn();
+        } else if let Some(next) = fade.next.take() {
+            next_state.set(next);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, FadePlugin));
+        app.init_state::<GameState>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        // The first update only starts the clock
+        app.update();
+        app
+    }
+
+    #[test]
+    fn fade_out_switches_state_once_black() {
+        let mut app = app();
+        let mut commands = app.world_mut().commands();
+        spawn_screen_fade(&mut commands, ScreenFade::to_black(0.25, GameState::Menu), GameState::ProfileSelect);
+        app.world_mut().flush();
+
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::ProfileSelect);
+        let mut fades = app.world_mut().query::<&ScreenFade>();
+        assert!(fades.single(app.world()).unwrap().alpha() > 0.5);
+
+        app.update();
+        app.update();
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Menu);
+        // Leaving the state it was spawned in removes the overlay
+        assert!(fades.iter(app.world()).next().is_none());
+    }
+
+    #[test]
+    fn fade_in_clears_and_goes_away() {
+        let mut app = app();
+        let mut commands = app.world_mut().commands();
+        spawn_screen_fade(&mut commands, ScreenFade::from_black(0.25), GameState::ProfileSelect);
+        app.world_mut().flush();
+
+        let mut fades = app.world_mut().query::<&ScreenFade>();
+        assert_eq!(fades.single(app.world()).unwrap().alpha(), 1.0);
+        app.update();
+        assert!(fades.single(app.world()).unwrap().alpha() < 1.0);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(fades.iter(app.world()).next().is_none());
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::ProfileSelect);
+    }
+}
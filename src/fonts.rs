@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+/// Bevy's built-in font has no Cyrillic, so all UI text uses DejaVu Sans from `assets/fonts`.
+const REGULAR_FONT: &str = "fonts/DejaVuSans.ttf";
+const BOLD_FONT: &str = "fonts/DejaVuSans-Bold.ttf";
+
+/// Fonts loaded once at startup. The default handles fall back to Bevy's font,
+/// which is enough for tests that never render.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct UiFonts {
+    pub regular: Handle<Font>,
+    pub bold: Handle<Font>,
+}
+
+impl UiFonts {
+    pub fn load(asset_server: &AssetServer) -> Self {
+        Self {
+            regular: asset_server.load(REGULAR_FONT),
+            bold: asset_server.load(BOLD_FONT),
+        }
+    }
+
+    /// Buttons, HUD and other body text.
+    pub fn text(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.regular.clone(),
+            font_size,
+            ..default()
+        }
+    }
+
+    /// Screen titles.
+    pub fn title(&self, font_size: f32) -> TextFont {
+        TextFont {
+            font: self.bold.clone(),
+            font_size,
+            ..default()
+        }
+    }
+}
+
+pub struct FontsPlugin;
+
+impl Plugin for FontsPlugin {
+    fn build(&self, app: &mut App) {
+        // Inserted while building so screens spawned on entering the first state already have it
+        let fonts = UiFonts::load(app.world().resource::<AssetServer>());
+        app.insert_resource(fonts);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_fonts_parse() {
+        for path in [REGULAR_FONT, BOLD_FONT] {
+            let full_path = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), path);
+            let bytes = std::fs::read(&full_path).unwrap_or_else(|e| panic!("Failed to read {}: {}", full_path, e));
+            assert!(Font::try_from_bytes(bytes).is_ok(), "{} is not a font", path);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::level::{check_exit, playing_bundled_level, CurrentLevel, LevelCompleted};
+use crate::player::{Player, PlayerSystems};
+use crate::progress::profile_dir;
+use crate::settings::GameSettings;
+use crate::states::GameState;
+use crate::storage;
+
+/// Bump when the ghost layout changes; older files are still accepted.
+pub const GHOST_VERSION: u32 = 1;
+
+/// Player positions of the best attempt on a level, one sample per FixedUpdate tick.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GhostRun {
+    pub version: u32,
+    pub level: usize,
+    pub samples: Vec<(f32, f32)>,
+}
+
+impl GhostRun {
+    /// Fewer ticks from spawn to exit means a faster run.
+    fn beats(&self, other: &GhostRun) -> bool {
+        self.samples.len() < other.samples.len()
+    }
+}
+
+/// Positions of the current attempt.
+#[derive(Resource, Default)]
+struct GhostRecorder {
+    samples: Vec<(f32, f32)>,
+}
+
+/// The translucent sprite replaying the best attempt. Has no collider.
+#[derive(Component)]
+struct Ghost {
+    samples: Vec<(f32, f32)>,
+    tick: usize,
+}
+
+const GHOST_COLOR: Color = Color::srgba(0.6, 0.8, 1.0, 0.35);
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GhostRecorder>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (reset_ghost_recorder, spawn_ghost.run_if(playing_bundled_level)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (record_ghost_sample, advance_ghost)
+                    .after(PlayerSystems::Movement)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                save_best_ghost
+                    .after(check_exit)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn ghost_path(level: usize) -> PathBuf {
+    let dir = profile_dir().join("ghosts");
+    std::fs::create_dir_all(&dir).ok();
+    dir.join(format!("level_{:02}.json", level + 1))
+}
+
+fn save_ghost_to(run: &GhostRun, path: &Path) {
+    match serde_json::to_string(run) {
+        Ok(json) => {
+            if let Err(e) = storage::write_atomic(path, json.as_bytes()) {
+                error!("Failed to save ghost: {}", e);
+            } else {
+                info!("Ghost saved for level {} ({} samples)", run.level + 1, run.samples.len());
+            }
+        }
+        Err(e) => error!("Failed to serialize ghost: {}", e),
+    }
+}
+
+fn load_ghost_from(path: &Path) -> Option<GhostRun> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<GhostRun>(&contents) {
+        Ok(run) if run.version <= GHOST_VERSION => Some(run),
+        Ok(run) => {
+            error!("Ghost {:?} has unsupported version {}", path, run.version);
+            None
+        }
+        Err(e) => {
+            error!("Failed to parse ghost {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+fn reset_ghost_recorder(mut recorder: ResMut<GhostRecorder>) {
+    recorder.samples.clear();
+}
+
+fn spawn_ghost(mut commands: Commands, settings: Res<GameSettings>, current_level: Res<CurrentLevel>) {
+    if !settings.show_ghost {
+        return;
+    }
+    let Some(run) = load_ghost_from(&ghost_path(current_level.0)) else {
+        return;
+    };
+    let Some(&(x, y)) = run.samples.first() else {
+        return;
+    };
+
+    commands.spawn((
+        Ghost {
+            samples: run.samples,
+            tick: 0,
+        },
+        Sprite {
+            color: GHOST_COLOR,
+            custom_size: Some(Vec2::new(24.0, 32.0)),
+            ..default()
+        },
+        // Drawn over tiles, but no RigidBody or Collider: purely visual
+        Transform::from_xyz(x, y, 0.5),
+        DespawnOnExit::<GameState>(GameState::Playing),
+    ));
+}
+
+fn record_ghost_sample(
+    player_query: Query<&Transform, With<Player>>,
+    mut recorder: ResMut<GhostRecorder>,
+) {
+    if let Ok(transform) = player_query.single() {
+        recorder.samples.push((transform.translation.x, transform.translation.y));
+    }
+}
+
+fn advance_ghost(mut ghosts: Query<(&mut Ghost, &mut Transform, &mut Visibility)>) {
+    for (mut ghost, mut transform, mut visibility) in &mut ghosts {
+        match ghost.samples.get(ghost.tick) {
+            Some(&(x, y)) => {
+                transform.translation.x = x;
+                transform.translation.y = y;
+                ghost.tick += 1;
+            }
+            // The ghost reached the exit
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn save_best_ghost(mut completed: MessageReader<LevelCompleted>, recorder: Res<GhostRecorder>) {
+    for event in completed.read() {
+        // Invincibility or extra jumps would leave a path nobody can follow
+        if event.assisted {
+            continue;
+        }
+        let run = GhostRun {
+            version: GHOST_VERSION,
+            level: event.index,
+            samples: recorder.samples.clone(),
+        };
+        let path = ghost_path(event.index);
+        let is_best = load_ghost_from(&path).is_none_or(|best| run.beats(&best));
+        if is_best {
+            save_ghost_to(&run, &path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(samples: usize) -> GhostRun {
+        GhostRun {
+            version: GHOST_VERSION,
+            level: 0,
+            samples: (0..samples).map(|i| (i as f32, 0.0)).collect(),
+        }
+    }
+
+    #[test]
+    fn ghost_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ghost.json");
+
+        let ghost = run(3);
+        save_ghost_to(&ghost, &path);
+        assert_eq!(load_ghost_from(&path), Some(ghost));
+    }
+
+    #[test]
+    fn shorter_run_beats_longer() {
+        assert!(run(100).beats(&run(120)));
+        assert!(!run(120).beats(&run(100)));
+        assert!(!run(100).beats(&run(100)));
+    }
+
+    #[test]
+    fn ghost_follows_samples_then_hides() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_systems(Update, advance_ghost);
+
+        let entity = app
+            .world_mut()
+            .spawn((
+                Ghost {
+                    samples: vec![(10.0, 20.0), (30.0, 40.0)],
+                    tick: 0,
+                },
+                Transform::default(),
+                Visibility::Inherited,
+            ))
+            .id();
+
+        app.update();
+        app.update();
+        let transform = app.world().entity(entity).get::<Transform>().unwrap();
+        assert_eq!(transform.translation.truncate(), Vec2::new(30.0, 40.0));
+
+        app.update();
+        let visibility = app.world().entity(entity).get::<Visibility>().unwrap();
+        assert_eq!(*visibility, Visibility::Hidden);
+    }
+}
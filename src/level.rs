@@ -1,22 +1,54 @@
 use std::collections::HashSet;
+use std::path::PathBuf;
 
 use avian2d::prelude::*;
 use bevy::prelude::*;
 use serde::Deserialize;
 
-use crate::player::{Player, SpawnPoint, DeathCounter};
+use crate::accessibility::spawn_hazard_mark;
+use crate::assist::LevelAssisted;
+use crate::background::{BackgroundLayer, Backgrounds};
+use crate::fade::{fading_out, spawn_screen_fade, ScreenFade};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::player::{Player, PlayerDied, SpawnPoint, DeathCounter};
 use crate::progress::PlayerProgress;
+use crate::settings::GameSettings;
 use crate::states::GameState;
+use crate::tileset::{read_tileset_data, Tileset, TilesetData, Tilesets};
 
 #[derive(Deserialize)]
 pub struct LevelData {
-    #[allow(dead_code)]
     pub name: String,
     pub spawn: (f32, f32),
     pub exit: (f32, f32),
+    /// Background track under `assets/`: a generated `Tune` such as `"audio/music/level_01.ron"`,
+    /// or an audio file such as `"audio/music/level_01.ogg"`.
+    #[serde(default)]
+    pub music: Option<String>,
+    /// Tileset under `assets/`, e.g. `"tilesets/grass.ron"`. Without one tiles are flat colors.
+    #[serde(default)]
+    pub tileset: Option<String>,
+    /// Parallax layers behind the level, back to front. Without any the clear color shows.
+    #[serde(default)]
+    pub background: Vec<BackgroundLayer>,
     pub tiles: Vec<TileEntry>,
 }
 
+impl LevelData {
+    /// Checks what parsing can't, including the tileset's tile indices, so a broken
+    /// level is refused before it is played.
+    pub fn validate(&self) -> Result<(), String> {
+        for layer in &self.background {
+            layer.validate()?;
+        }
+        if let Some(tileset) = &self.tileset {
+            read_tileset_data(tileset)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Deserialize)]
 pub struct TileEntry {
     pub x: i32,
@@ -45,7 +77,35 @@ pub struct DeathHud;
 #[derive(Resource)]
 pub struct CurrentLevel(pub usize);
 
+/// A level file outside `LEVELS`, opened from the command line. While set it
+/// replaces the current level and is replayed on completion without touching progress.
+#[derive(Resource, Default)]
+pub struct CustomLevel(pub Option<PathBuf>);
+
+/// Music track of the loaded level, picked up by the audio plugin.
+#[derive(Resource, Default)]
+pub struct LevelMusic(pub Option<String>);
+
+/// Run condition: false while a custom level is being played.
+pub fn playing_bundled_level(custom: Res<CustomLevel>) -> bool {
+    custom.0.is_none()
+}
+
+/// Written when the player reaches the exit of level `index`, or skips it.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct LevelCompleted {
+    pub index: usize,
+    /// An assist was used, so the completion sets no records.
+    pub assisted: bool,
+}
+
+/// Moves on to the next bundled level as if it was completed with an assist.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct SkipLevel;
+
 const TILE_SIZE: f32 = 32.0;
+/// Fade to black between reaching the exit and the transition screen.
+const EXIT_FADE_DURATION: f32 = 0.4;
 
 pub const LEVELS: &[&str] = &[
     "assets/levels/level_01.ron",
@@ -60,24 +120,54 @@ pub struct LevelPlugin;
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(CurrentLevel(0))
+            .init_resource::<CustomLevel>()
+            .init_resource::<LevelMusic>()
+            .add_message::<LevelCompleted>()
+            .add_message::<SkipLevel>()
             .add_systems(OnEnter(GameState::Playing), (load_level, spawn_hud))
             .add_systems(
                 Update,
-                (check_exit, check_spikes, update_hud).run_if(in_state(GameState::Playing)),
+                (
+                    // The level holds still while it fades out after the exit
+                    (check_exit, check_spikes).run_if(not(fading_out)),
+                    update_hud,
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
-            .add_systems(OnEnter(GameState::LevelTransition), transition_to_playing);
+            .add_systems(Update, skip_level.run_if(playing_bundled_level))
+            .add_systems(OnEnter(GameState::LevelSelect), clear_custom_level);
     }
 }
 
-/// Groups platform tiles into horizontal runs for merged colliders.
-/// Returns a list of (start_x, y, count) tuples.
-fn merge_platform_runs(tiles: &[TileEntry]) -> Vec<(i32, i32, usize)> {
-    let platforms: HashSet<(i32, i32)> = tiles
+/// Name of bundled level `index`, for screens shown before it loads.
+pub fn level_name(index: usize) -> Option<String> {
+    let contents = std::fs::read_to_string(LEVELS.get(index)?).ok()?;
+    ron::from_str::<LevelData>(&contents).ok().map(|level| level.name)
+}
+
+/// Grid cells holding a platform, shared by collider merging and auto-tiling.
+fn platform_cells(tiles: &[TileEntry]) -> HashSet<(i32, i32)> {
+    tiles
         .iter()
         .filter(|t| matches!(t.kind, TileKind::Platform))
         .map(|t| (t.x, t.y))
-        .collect();
+        .collect()
+}
+
+/// Which sides of `(x, y)` have another platform: 1 above, 2 right, 4 below, 8 left.
+/// Picks the edge or corner tile from `TilesetData::platform`.
+fn neighbour_mask(platforms: &HashSet<(i32, i32)>, x: i32, y: i32) -> usize {
+    [(0, 1), (1, 0), (0, -1), (-1, 0)]
+        .iter()
+        .enumerate()
+        .filter(|(_, (dx, dy))| platforms.contains(&(x + dx, y + dy)))
+        .map(|(bit, _)| 1 << bit)
+        .sum()
+}
 
+/// Groups platform cells into horizontal runs for merged colliders.
+/// Returns a list of (start_x, y, count) tuples.
+fn merge_platform_runs(platforms: &HashSet<(i32, i32)>) -> Vec<(i32, i32, usize)> {
     let mut runs = Vec::new();
     let mut visited: HashSet<(i32, i32)> = HashSet::new();
 
@@ -115,15 +205,57 @@ fn merge_platform_runs(tiles: &[TileEntry]) -> Vec<(i32, i32, usize)> {
     runs
 }
 
-fn load_level(mut commands: Commands, current_level: Res<CurrentLevel>, mut spawn_point: ResMut<SpawnPoint>) {
+/// A tile from `tileset` multiplied by `tint`, or a flat `color` square without one.
+/// Spikes and the exit are drawn light in the image, so tinting keeps the palette colors.
+fn tile_sprite(
+    tileset: Option<&Tileset>,
+    index: impl FnOnce(&TilesetData) -> usize,
+    tint: Color,
+    color: Color,
+) -> Sprite {
+    match tileset {
+        Some(tileset) => tileset.sprite(index(&tileset.data), TILE_SIZE, tint),
+        None => Sprite {
+            color,
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            ..default()
+        },
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn load_level(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    custom: Res<CustomLevel>,
+    mut spawn_point: ResMut<SpawnPoint>,
+    mut music: ResMut<LevelMusic>,
+    settings: Res<GameSettings>,
+    mut tilesets: Tilesets,
+    mut backgrounds: Backgrounds,
+) {
     let index = current_level.0;
-    let contents = std::fs::read_to_string(LEVELS[index])
-        .unwrap_or_else(|e| panic!("Failed to read level file {}: {}", LEVELS[index], e));
+    let colors = settings.palette.colors();
+    let path = match &custom.0 {
+        Some(path) => path.clone(),
+        None => PathBuf::from(LEVELS[index]),
+    };
+    let contents = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read level file {}: {}", path.display(), e));
     let level: LevelData = ron::from_str(&contents)
-        .unwrap_or_else(|e| panic!("Failed to parse level file {}: {}", LEVELS[index], e));
+        .unwrap_or_else(|e| panic!("Failed to parse level file {}: {}", path.display(), e));
+    // A broken tileset falls back to flat colors in `Tilesets::get`, so only the background is fatal
+    if let Err(e) = level.background.iter().try_for_each(BackgroundLayer::validate) {
+        panic!("Invalid level file {}: {}", path.display(), e);
+    }
 
     info!("Loaded level {} '{}' ({} tiles)", index + 1, level.name, level.tiles.len());
 
+    music.0 = level.music.clone();
+    let tileset = level.tileset.as_deref().and_then(|path| tilesets.get(path));
+    let platforms = platform_cells(&level.tiles);
+    backgrounds.spawn(&mut commands, &level.background);
+
     // Set spawn point
     spawn_point.0 = Vec2::new(level.spawn.0 * TILE_SIZE, level.spawn.1 * TILE_SIZE);
 
@@ -132,25 +264,18 @@ fn load_level(mut commands: Commands, current_level: Res<CurrentLevel>, mut spaw
         let pos = Vec3::new(tile.x as f32 * TILE_SIZE, tile.y as f32 * TILE_SIZE, 0.0);
         match tile.kind {
             TileKind::Platform => {
+                let mask = neighbour_mask(&platforms, tile.x, tile.y);
                 commands.spawn((
                     Platform,
-                    Sprite {
-                        color: Color::srgb(0.4, 0.4, 0.4),
-                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                        ..default()
-                    },
+                    tile_sprite(tileset.as_ref(), |t| t.platform[mask], Color::WHITE, colors.platform),
                     Transform::from_translation(pos),
                     DespawnOnExit::<GameState>(GameState::Playing),
                 ));
             }
             TileKind::Spikes => {
-                commands.spawn((
+                let mut spikes = commands.spawn((
                     Spikes,
-                    Sprite {
-                        color: Color::srgb(0.9, 0.2, 0.2),
-                        custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-                        ..default()
-                    },
+                    tile_sprite(tileset.as_ref(), |t| t.spikes, colors.spikes, colors.spikes),
                     Transform::from_translation(pos),
                     RigidBody::Static,
                     Collider::rectangle(TILE_SIZE, TILE_SIZE),
@@ -158,12 +283,15 @@ fn load_level(mut commands: Commands, current_level: Res<CurrentLevel>, mut spaw
                     CollidingEntities::default(),
                     DespawnOnExit::<GameState>(GameState::Playing),
                 ));
+                if settings.hazard_shapes {
+                    spikes.with_children(|mark| spawn_hazard_mark(mark, TILE_SIZE, colors.hazard_mark));
+                }
             }
         }
     }
 
     // Spawn merged platform colliders (physics only, no sprite)
-    for (start_x, y, count) in merge_platform_runs(&level.tiles) {
+    for (start_x, y, count) in merge_platform_runs(&platforms) {
         let width = count as f32 * TILE_SIZE;
         let center_x = start_x as f32 * TILE_SIZE + (width - TILE_SIZE) / 2.0;
         let center_y = y as f32 * TILE_SIZE;
@@ -181,11 +309,7 @@ fn load_level(mut commands: Commands, current_level: Res<CurrentLevel>, mut spaw
     let exit_pos = Vec3::new(level.exit.0 * TILE_SIZE, level.exit.1 * TILE_SIZE, 0.0);
     commands.spawn((
         Exit,
-        Sprite {
-            color: Color::srgb(0.2, 0.9, 0.2),
-            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
-            ..default()
-        },
+        tile_sprite(tileset.as_ref(), |t| t.exit, colors.exit, colors.exit),
         Transform::from_translation(exit_pos),
         RigidBody::Static,
         Collider::rectangle(TILE_SIZE, TILE_SIZE),
@@ -195,48 +319,99 @@ fn load_level(mut commands: Commands, current_level: Res<CurrentLevel>, mut spaw
     ));
 }
 
-fn check_exit(
+#[allow(clippy::too_many_arguments)]
+pub fn check_exit(
+    mut commands: Commands,
     exit_query: Query<&CollidingEntities, With<Exit>>,
     player_query: Query<(), With<Player>>,
     mut current_level: ResMut<CurrentLevel>,
+    custom: Res<CustomLevel>,
     mut progress: ResMut<PlayerProgress>,
     mut next_state: ResMut<NextState<GameState>>,
     mut counter: ResMut<DeathCounter>,
+    mut completed: MessageWriter<LevelCompleted>,
+    assisted: Res<LevelAssisted>,
 ) {
     for colliding in &exit_query {
         for &entity in colliding.iter() {
             if player_query.get(entity).is_ok() {
-                counter.total += counter.current_level;
-
-                current_level.0 += 1;
-                if current_level.0 > progress.max_unlocked_level {
-                    progress.max_unlocked_level = current_level.0;
-                    crate::progress::save_progress(&progress);
-                }
-                info!("Level {} completed (deaths: {})", current_level.0, counter.current_level);
-                if current_level.0 < LEVELS.len() {
+                if custom.0.is_some() {
+                    info!("Custom level completed (deaths: {}), restarting", counter.current_level);
                     next_state.set(GameState::LevelTransition);
-                } else {
-                    next_state.set(GameState::Victory);
+                    return;
                 }
+                completed.write(LevelCompleted {
+                    index: current_level.0,
+                    assisted: assisted.0,
+                });
+                info!("Level {} completed (deaths: {})", current_level.0 + 1, counter.current_level);
+                let next = advance_level(&mut current_level, &mut progress, &mut counter);
+                spawn_screen_fade(
+                    &mut commands,
+                    ScreenFade::to_black(EXIT_FADE_DURATION, next),
+                    GameState::Playing,
+                );
                 return;
             }
         }
     }
 }
 
-fn transition_to_playing(mut next_state: ResMut<NextState<GameState>>) {
-    next_state.set(GameState::Playing);
+/// Moves on to the level after the current one, unlocking it, and returns the state to go
+/// to next: LevelTransition, or Victory after the last level. The unlock is saved with the
+/// level's statistics.
+fn advance_level(
+    current_level: &mut CurrentLevel,
+    progress: &mut PlayerProgress,
+    counter: &mut DeathCounter,
+) -> GameState {
+    counter.total += counter.current_level;
+    current_level.0 += 1;
+    if current_level.0 > progress.max_unlocked_level {
+        progress.max_unlocked_level = current_level.0;
+    }
+    if current_level.0 < LEVELS.len() {
+        GameState::LevelTransition
+    } else {
+        GameState::Victory
+    }
 }
 
-fn spawn_hud(mut commands: Commands) {
+fn skip_level(
+    mut skips: MessageReader<SkipLevel>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut progress: ResMut<PlayerProgress>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut counter: ResMut<DeathCounter>,
+    mut completed: MessageWriter<LevelCompleted>,
+) {
+    if skips.read().count() == 0 {
+        return;
+    }
+    completed.write(LevelCompleted {
+        index: current_level.0,
+        assisted: true,
+    });
+    info!("Level {} skipped", current_level.0 + 1);
+    // Skipping happens from the pause menu, so there's no level on screen to fade out
+    next_state.set(advance_level(&mut current_level, &mut progress, &mut counter));
+}
+
+/// Picking a level from the menu goes back to the bundled levels.
+fn clear_custom_level(mut custom: ResMut<CustomLevel>) {
+    custom.0 = None;
+}
+
+fn spawn_hud(
+    mut commands: Commands,
+    counter: Res<DeathCounter>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
     commands.spawn((
         DeathHud,
-        Text::new("Deaths: 0"),
-        TextFont {
-            font_size: 24.0,
-            ..default()
-        },
+        Text::new(locale.format("hud.deaths", &[("count", &counter.current_level)])),
+        fonts.text(24.0),
         TextColor(Color::WHITE),
         Node {
             position_type: PositionType::Absolute,
@@ -250,11 +425,12 @@ fn spawn_hud(mut commands: Commands) {
 
 fn update_hud(
     counter: Res<DeathCounter>,
+    locale: Res<Locale>,
     mut query: Query<&mut Text, With<DeathHud>>,
 ) {
-    if counter.is_changed() {
+    if counter.is_changed() || locale.is_changed() {
         for mut text in &mut query {
-            **text = format!("Deaths: {}", counter.current_level);
+            **text = locale.format("hud.deaths", &[("count", &counter.current_level)]);
         }
     }
 }
@@ -265,15 +441,23 @@ fn check_spikes(
     mut player_transform_query: Query<(&mut Transform, &mut LinearVelocity), With<Player>>,
     spawn_point: Res<SpawnPoint>,
     mut counter: ResMut<DeathCounter>,
+    mut died: MessageWriter<PlayerDied>,
+    settings: Res<GameSettings>,
 ) {
+    if settings.invincible {
+        return;
+    }
     for colliding in &spikes_query {
         for &entity in colliding.iter() {
             if player_query.get(entity).is_ok() {
+                let mut position = Vec2::ZERO;
                 for (mut transform, mut velocity) in &mut player_transform_query {
+                    position = transform.translation.truncate();
                     transform.translation = spawn_point.0.extend(0.0);
                     *velocity = LinearVelocity::ZERO;
                 }
                 counter.current_level += 1;
+                died.write(PlayerDied { position });
                 debug!("Death by spikes (level deaths: {})", counter.current_level);
                 return;
             }
@@ -284,6 +468,7 @@ fn check_spikes(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::background::LayerFill;
 
     fn tile(x: i32, y: i32, kind: TileKind) -> TileEntry {
         TileEntry { x, y, kind }
@@ -296,7 +481,7 @@ mod tests {
             tile(1, 0, TileKind::Platform),
             tile(2, 0, TileKind::Platform),
         ];
-        let runs = merge_platform_runs(&tiles);
+        let runs = merge_platform_runs(&platform_cells(&tiles));
         assert_eq!(runs.len(), 1);
         assert_eq!(runs[0], (0, 0, 3));
     }
@@ -309,7 +494,7 @@ mod tests {
             tile(5, 0, TileKind::Platform),
             tile(6, 0, TileKind::Platform),
         ];
-        let runs = merge_platform_runs(&tiles);
+        let runs = merge_platform_runs(&platform_cells(&tiles));
         assert_eq!(runs.len(), 2);
         assert!(runs.contains(&(0, 0, 2)));
         assert!(runs.contains(&(5, 0, 2)));
@@ -322,7 +507,7 @@ mod tests {
             tile(1, 0, TileKind::Spikes),
             tile(2, 0, TileKind::Platform),
         ];
-        let runs = merge_platform_runs(&tiles);
+        let runs = merge_platform_runs(&platform_cells(&tiles));
         assert_eq!(runs.len(), 2);
         assert!(runs.contains(&(0, 0, 1)));
         assert!(runs.contains(&(2, 0, 1)));
@@ -336,7 +521,7 @@ mod tests {
             tile(0, 1, TileKind::Platform),
             tile(1, 1, TileKind::Platform),
         ];
-        let runs = merge_platform_runs(&tiles);
+        let runs = merge_platform_runs(&platform_cells(&tiles));
         assert_eq!(runs.len(), 2);
         assert!(runs.contains(&(0, 0, 2)));
         assert!(runs.contains(&(0, 1, 2)));
@@ -344,18 +529,38 @@ mod tests {
 
     #[test]
     fn empty_tiles() {
-        let runs = merge_platform_runs(&[]);
+        let runs = merge_platform_runs(&HashSet::new());
         assert!(runs.is_empty());
     }
 
     #[test]
     fn single_platform() {
         let tiles = vec![tile(5, 3, TileKind::Platform)];
-        let runs = merge_platform_runs(&tiles);
+        let runs = merge_platform_runs(&platform_cells(&tiles));
         assert_eq!(runs.len(), 1);
         assert_eq!(runs[0], (5, 3, 1));
     }
 
+    #[test]
+    fn neighbour_mask_finds_edges_and_corners() {
+        // ###
+        // ##
+        let tiles = vec![
+            tile(0, 1, TileKind::Platform),
+            tile(1, 1, TileKind::Platform),
+            tile(2, 1, TileKind::Platform),
+            tile(0, 0, TileKind::Platform),
+            tile(1, 0, TileKind::Platform),
+            tile(2, 0, TileKind::Spikes),
+        ];
+        let platforms = platform_cells(&tiles);
+        assert_eq!(neighbour_mask(&platforms, 0, 1), 2 | 4);
+        assert_eq!(neighbour_mask(&platforms, 1, 1), 2 | 4 | 8);
+        assert_eq!(neighbour_mask(&platforms, 2, 1), 8);
+        assert_eq!(neighbour_mask(&platforms, 1, 0), 1 | 8);
+        assert_eq!(neighbour_mask(&platforms, 5, 5), 0);
+    }
+
     #[test]
     fn parse_level_from_ron() {
         let ron_str = r#"LevelData(
@@ -387,6 +592,23 @@ mod tests {
                 .unwrap_or_else(|e| panic!("Failed to parse {}: {}", full_path, e));
             assert!(!level.name.is_empty(), "Level {} has empty name", level_path);
             assert!(!level.tiles.is_empty(), "Level {} has no tiles", level_path);
+            if let Err(e) = level.validate() {
+                panic!("Level {} is invalid: {}", level_path, e);
+            }
+            if let Some(music) = &level.music {
+                let music_path = format!("{}/assets/{}", manifest_dir, music);
+                assert!(std::path::Path::new(&music_path).exists(), "{} is missing", music_path);
+            }
+            if let Some(tileset) = &level.tileset {
+                let tileset_path = format!("{}/assets/{}", manifest_dir, tileset);
+                assert!(std::path::Path::new(&tileset_path).exists(), "{} is missing", tileset_path);
+            }
+            for layer in &level.background {
+                if let LayerFill::Image { path, .. } = &layer.fill {
+                    let image_path = format!("{}/assets/{}", manifest_dir, path);
+                    assert!(std::path::Path::new(&image_path).exists(), "{} is missing", image_path);
+                }
+            }
         }
     }
 }
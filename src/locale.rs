@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::GameSettings;
+
+/// Languages shipped in `assets/lang`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Russian,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Russian];
+
+    /// Name of the string table, `assets/lang/<code>.ron`.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Russian => "ru",
+        }
+    }
+
+    /// Shown in the language picker, so each one is readable to its speakers.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Russian => "Русский",
+        }
+    }
+}
+
+/// UI strings of the selected language. Keys missing from it fall back to
+/// English, then to the key itself.
+#[derive(Resource, Debug, Clone)]
+pub struct Locale {
+    pub language: Language,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::load(Language::English)
+    }
+}
+
+impl Locale {
+    pub fn load(language: Language) -> Self {
+        let fallback = load_strings(Language::English);
+        let strings = if language == Language::English {
+            fallback.clone()
+        } else {
+            load_strings(language)
+        };
+        Self {
+            language,
+            strings,
+            fallback,
+        }
+    }
+
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map(String::as_str)
+            .unwrap_or(key)
+    }
+
+    /// `get`, with each `{name}` replaced by its value from `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+fn lang_path(language: Language) -> String {
+    format!("assets/lang/{}.ron", language.code())
+}
+
+fn load_strings(language: Language) -> HashMap<String, String> {
+    let path = lang_path(language);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to read string table {}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+    match ron::from_str(&contents) {
+        Ok(strings) => strings,
+        Err(e) => {
+            error!("Failed to parse string table {}: {}", path, e);
+            HashMap::new()
+        }
+    }
+}
+
+pub struct LocalePlugin;
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        // Only the window settings are known this early; the profile's language is loaded
+        // with the profile, so the picker shown before one is chosen is in English
+        let language = app
+            .world()
+            .get_resource::<GameSettings>()
+            .map(|s| s.language)
+            .unwrap_or_default();
+        app.insert_resource(Locale::load(language)).add_systems(
+            PostUpdate,
+            reload_locale.run_if(resource_changed::<GameSettings>),
+        );
+    }
+}
+
+/// Follows the language setting, including when unsaved changes are discarded.
+fn reload_locale(settings: Res<GameSettings>, mut locale: ResMut<Locale>) {
+    if locale.language != settings.language {
+        *locale = Locale::load(settings.language);
+        info!("Language set to {}", settings.language.native_name());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::actions::Action;
+
+    fn keys(language: Language) -> BTreeSet<String> {
+        load_strings(language).into_keys().collect()
+    }
+
+    /// Keys passed as literals to `locale.get` and `locale.format` in the sources.
+    fn keys_used_in_sources() -> BTreeSet<String> {
+        let src = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+        let mut used = BTreeSet::new();
+        for entry in std::fs::read_dir(src).unwrap() {
+            let path = entry.unwrap().path();
+            // This file's own tests look up keys that don't exist on purpose
+            if path.ends_with("locale.rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(path).unwrap();
+            for call in ["locale.get(", "locale.format("] {
+                for (start, _) in source.match_indices(call) {
+                    // Keys computed at runtime are checked where they are defined
+                    let Some(rest) = source[start + call.len()..].trim_start().strip_prefix('"') else {
+                        continue;
+                    };
+                    let end = rest.find('"').unwrap();
+                    used.insert(rest[..end].to_string());
+                }
+            }
+        }
+        used
+    }
+
+    #[test]
+    fn every_language_has_every_key() {
+        let english = keys(Language::English);
+        assert!(!english.is_empty(), "English string table is empty");
+
+        for language in Language::ALL {
+            let table = keys(language);
+            let missing: Vec<_> = english.difference(&table).collect();
+            let extra: Vec<_> = table.difference(&english).collect();
+            assert!(missing.is_empty(), "{:?} is missing {:?}", language, missing);
+            assert!(extra.is_empty(), "{:?} has unknown keys {:?}", language, extra);
+        }
+    }
+
+    #[test]
+    fn every_used_key_exists() {
+        let english = keys(Language::English);
+        let mut used = keys_used_in_sources();
+        used.extend(Action::ALL.iter().map(|a| a.label_key().to_string()));
+
+        let missing: Vec<_> = used.difference(&english).collect();
+        assert!(missing.is_empty(), "keys missing from en.ron: {:?}", missing);
+    }
+
+    #[test]
+    fn missing_key_falls_back() {
+        let locale = Locale::load(Language::Russian);
+        assert_eq!(locale.get("menu.play"), "Играть");
+        assert_eq!(locale.get("no.such.key"), "no.such.key");
+        assert_eq!(locale.format("hud.deaths", &[("count", &3)]), "Смертей: 3");
+    }
+}
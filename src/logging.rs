@@ -85,17 +85,14 @@ impl tracing::field::Visit for MessageVisitor {
 }
 
 fn log_path() -> std::path::PathBuf {
-    let base = dirs::data_dir().unwrap_or_else(|| std::path::PathBuf::from("."));
-    let dir = base.join("simple_platformer");
-    std::fs::create_dir_all(&dir).ok();
-    dir.join("game.log")
+    crate::progress::data_dir().join("game.log")
 }
 
 fn flush_to_file(buffer: &LogRingBuffer) {
     if let Ok(buf) = buffer.0.lock() {
         let content: String = buf.iter().map(|s| s.as_str()).collect::<Vec<_>>().join("\n");
         let path = log_path();
-        std::fs::write(path, content).ok();
+        crate::storage::write_atomic(&path, content.as_bytes()).ok();
     }
 }
 
@@ -132,7 +129,8 @@ impl Plugin for LoggingPlugin {
 
 /// Call from main() BEFORE App::new() to set up tracing subscriber.
 /// Returns the ring buffer for insertion into the Bevy app.
-pub fn setup_tracing() -> LogRingBuffer {
+/// `filter` (from `--log`) takes precedence over `RUST_LOG`.
+pub fn setup_tracing(filter: Option<&str>) -> LogRingBuffer {
     use tracing_subscriber::layer::SubscriberExt;
     use tracing_subscriber::util::SubscriberInitExt;
     use tracing_subscriber::EnvFilter;
@@ -142,8 +140,16 @@ pub fn setup_tracing() -> LogRingBuffer {
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::fmt::layer().with_filter(
-                EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| "simple_platformer=info,wgpu=error,bevy=warn".parse().unwrap()),
+                filter
+                    .and_then(|f| match EnvFilter::try_new(f) {
+                        Ok(filter) => Some(filter),
+                        Err(e) => {
+                            eprintln!("Invalid log filter '{}': {}", f, e);
+                            None
+                        }
+                    })
+                    .or_else(|| EnvFilter::try_from_default_env().ok())
+                    .unwrap_or_else(|| "simple_platformer=info,wgpu=error,bevy=warn".parse().unwrap()),
             ),
         )
         .with(RingBufferLayer::new(ring_buffer.clone()))
@@ -1,32 +1,98 @@
+mod accessibility;
+mod actions;
+mod animation;
+mod assist;
+mod audio;
+mod background;
 mod camera;
+mod cli;
+mod controls;
+mod fade;
+mod fonts;
+mod ghost;
 mod level;
 mod level_select;
+mod locale;
 mod logging;
 mod menu;
+mod particles;
 mod pause;
 mod physics;
 mod player;
+mod profiles;
 mod progress;
+mod replay;
 mod settings;
+mod speedrun;
 mod states;
+mod statistics;
+mod storage;
+mod tileset;
+mod transition;
 mod victory;
+mod widgets;
 
+use accessibility::AccessibilityPlugin;
+use actions::ActionsPlugin;
+use animation::AnimationPlugin;
+use assist::AssistPlugin;
+use audio::GameAudioPlugin;
+use background::BackgroundPlugin;
 use bevy::prelude::*;
+use bevy::window::VideoModeSelection;
 use camera::CameraPlugin;
+use cli::CliPlugin;
+use controls::ControlsPlugin;
+use fade::FadePlugin;
+use fonts::FontsPlugin;
+use ghost::GhostPlugin;
 use level::LevelPlugin;
 use level_select::LevelSelectPlugin;
+use locale::LocalePlugin;
 use logging::{LogBuffer, LoggingPlugin};
 use menu::MenuPlugin;
+use particles::ParticlesPlugin;
 use pause::PausePlugin;
 use physics::PhysicsPlugin;
 use player::PlayerPlugin;
+use profiles::ProfilePlugin;
 use progress::ProgressPlugin;
+use replay::ReplayPlugin;
 use settings::SettingsPlugin;
+use speedrun::SpeedrunPlugin;
 use states::StatesPlugin;
+use statistics::StatisticsPlugin;
+use tileset::TilesetPlugin;
+use transition::TransitionPlugin;
 use victory::VictoryPlugin;
+use widgets::WidgetsPlugin;
 
 fn main() {
-    let ring_buffer = logging::setup_tracing();
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if let Some(dir) = &args.data_dir {
+        progress::set_data_dir(dir.clone());
+    }
+
+    let ring_buffer = logging::setup_tracing(args.log_filter.as_deref());
+    // Read before the window exists so it opens with the saved size and mode
+    let mut settings = settings::load_settings();
+    if let Some(fullscreen) = args.fullscreen {
+        settings.display_mode = if fullscreen {
+            settings::DisplayMode::Borderless
+        } else {
+            settings::DisplayMode::Windowed
+        };
+    }
 
     App::new()
         .add_plugins(
@@ -34,7 +100,9 @@ fn main() {
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "SimplePlatformer".to_string(),
-                        resolution: (1280u32, 720u32).into(),
+                        resolution: settings.resolution.into(),
+                        mode: settings::window_mode(settings.display_mode, VideoModeSelection::Current),
+                        present_mode: settings::present_mode(settings.vsync),
                         ..default()
                     }),
                     ..default()
@@ -42,17 +110,39 @@ fn main() {
                 .disable::<bevy::log::LogPlugin>(),
         )
         .insert_resource(LogBuffer(ring_buffer))
+        .insert_resource(settings)
+        .insert_resource(args)
         .add_plugins(LoggingPlugin)
+        .add_plugins(LocalePlugin)
+        .add_plugins(FontsPlugin)
         .add_plugins(StatesPlugin)
+        .add_plugins(ActionsPlugin)
+        .add_plugins(FadePlugin)
         .add_plugins(CameraPlugin)
+        .add_plugins(GameAudioPlugin)
+        .add_plugins(CliPlugin)
         .add_plugins(PhysicsPlugin)
+        .add_plugins(TilesetPlugin)
         .add_plugins(LevelPlugin)
+        .add_plugins(BackgroundPlugin)
         .add_plugins(LevelSelectPlugin)
         .add_plugins(PlayerPlugin)
+        .add_plugins(AnimationPlugin)
+        .add_plugins(ParticlesPlugin)
         .add_plugins(ProgressPlugin)
+        .add_plugins(ProfilePlugin)
+        .add_plugins(ReplayPlugin)
+        .add_plugins(GhostPlugin)
+        .add_plugins(WidgetsPlugin)
         .add_plugins(MenuPlugin)
         .add_plugins(PausePlugin)
         .add_plugins(SettingsPlugin)
+        .add_plugins(ControlsPlugin)
+        .add_plugins(AccessibilityPlugin)
+        .add_plugins(AssistPlugin)
+        .add_plugins(SpeedrunPlugin)
+        .add_plugins(StatisticsPlugin)
         .add_plugins(VictoryPlugin)
+        .add_plugins(TransitionPlugin)
         .run();
 }
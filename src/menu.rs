@@ -1,42 +1,44 @@
 use bevy::ecs::message::MessageWriter;
 use bevy::prelude::*;
 
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::profiles::ActiveProfile;
 use crate::states::{GameState, SettingsOrigin};
+use crate::widgets::{spawn_menu, DialogClosed, MenuEntry, MenuEvent, MenuStyle, MenuSystems, OpenDialog};
 
-/// Tracks which menu button is currently selected (0-indexed).
-#[derive(Resource, Debug, Clone, Default)]
-pub struct SelectedMenuItem(pub usize);
+const EXIT_DIALOG: &str = "exit_game";
 
 /// Component attached to each menu button to identify its action.
 #[derive(Component, Debug, Clone, PartialEq, Eq)]
 pub enum MenuAction {
     StartGame,
+    Profiles,
+    Statistics,
     Settings,
     Exit,
 }
 
-const MENU_ITEMS: usize = 3;
-const COLOR_SELECTED: Color = Color::srgb(0.3, 0.3, 0.7);
-const COLOR_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
-
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SelectedMenuItem>()
-            .add_systems(OnEnter(GameState::Menu), setup_menu)
+        app.add_systems(OnEnter(GameState::Menu), setup_menu)
             .add_systems(
                 Update,
-                (menu_navigation, menu_mouse, menu_highlight, menu_action)
-                    .chain()
+                (menu_action, menu_exit_confirm)
+                    .in_set(MenuSystems::Respond)
                     .run_if(in_state(GameState::Menu)),
             );
     }
 }
 
-fn setup_menu(mut commands: Commands, mut selected: ResMut<SelectedMenuItem>) {
-    selected.0 = 0;
-
+fn setup_menu(
+    mut commands: Commands,
+    profile: Res<ActiveProfile>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
     commands
         .spawn((
             Node {
@@ -52,126 +54,77 @@ fn setup_menu(mut commands: Commands, mut selected: ResMut<SelectedMenuItem>) {
         .with_children(|parent| {
             // Title
             parent.spawn((
-                Text::new("SIMPLE PLATFORMER"),
-                TextFont {
-                    font_size: 60.0,
+                Text::new(locale.get("menu.title")),
+                fonts.title(60.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
                     ..default()
                 },
-                TextColor(Color::WHITE),
+            ));
+
+            parent.spawn((
+                Text::new(locale.format("menu.profile", &[("name", &profile.0)])),
+                fonts.text(20.0),
+                TextColor(Color::srgb(0.6, 0.6, 0.6)),
                 Node {
-                    margin: UiRect::bottom(Val::Px(40.0)),
+                    margin: UiRect::bottom(Val::Px(30.0)),
                     ..default()
                 },
             ));
 
             // Buttons
-            let buttons = [
-                ("Play", MenuAction::StartGame),
-                ("Settings", MenuAction::Settings),
-                ("Quit", MenuAction::Exit),
+            let buttons = vec![
+                MenuEntry::button(MenuAction::StartGame, locale.get("menu.play")),
+                MenuEntry::button(MenuAction::Profiles, locale.get("menu.profiles")),
+                MenuEntry::button(MenuAction::Statistics, locale.get("menu.statistics")),
+                MenuEntry::button(MenuAction::Settings, locale.get("menu.settings")),
+                MenuEntry::button(MenuAction::Exit, locale.get("menu.quit")),
             ];
-
-            for (label, action) in buttons {
-                parent
-                    .spawn((
-                        Button,
-                        action,
-                        Node {
-                            width: Val::Px(300.0),
-                            height: Val::Px(50.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            margin: UiRect::vertical(Val::Px(5.0)),
-                            ..default()
-                        },
-                        BackgroundColor(COLOR_NORMAL),
-                    ))
-                    .with_children(|btn| {
-                        btn.spawn((
-                            Text::new(label),
-                            TextFont {
-                                font_size: 24.0,
-                                ..default()
-                            },
-                            TextColor(Color::WHITE),
-                        ));
-                    });
-            }
+            spawn_menu(parent, &fonts, MenuStyle::default(), buttons);
         });
 }
 
-fn menu_navigation(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut selected: ResMut<SelectedMenuItem>,
-) {
-    if keyboard.just_pressed(KeyCode::ArrowUp) {
-        selected.0 = if selected.0 == 0 {
-            MENU_ITEMS - 1
-        } else {
-            selected.0 - 1
-        };
-    }
-    if keyboard.just_pressed(KeyCode::ArrowDown) {
-        selected.0 = (selected.0 + 1) % MENU_ITEMS;
-    }
-}
-
-fn menu_mouse(
-    mut selected: ResMut<SelectedMenuItem>,
-    buttons: Query<(&MenuAction, &Interaction), Changed<Interaction>>,
-) {
-    let actions_order = [MenuAction::StartGame, MenuAction::Settings, MenuAction::Exit];
-
-    for (action, interaction) in &buttons {
-        if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
-            let index = actions_order.iter().position(|a| a == action).unwrap_or(0);
-            selected.0 = index;
-        }
-    }
-}
-
-fn menu_highlight(
-    selected: Res<SelectedMenuItem>,
-    mut buttons: Query<(&MenuAction, &mut BackgroundColor)>,
-) {
-    let actions_order = [MenuAction::StartGame, MenuAction::Settings, MenuAction::Exit];
-
-    for (action, mut bg) in &mut buttons {
-        let index = actions_order.iter().position(|a| a == action).unwrap_or(0);
-        if index == selected.0 {
-            *bg = BackgroundColor(COLOR_SELECTED);
-        } else {
-            *bg = BackgroundColor(COLOR_NORMAL);
-        }
-    }
-}
-
 fn menu_action(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    selected: Res<SelectedMenuItem>,
+    mut events: MessageReader<MenuEvent>,
+    buttons: Query<&MenuAction>,
     mut next_state: ResMut<NextState<GameState>>,
     mut settings_origin: ResMut<SettingsOrigin>,
-    mut exit_events: MessageWriter<AppExit>,
-    buttons: Query<(&MenuAction, &Interaction)>,
+    mut dialogs: MessageWriter<OpenDialog>,
+    locale: Res<Locale>,
 ) {
-    let enter = keyboard.just_pressed(KeyCode::Enter);
-    let clicked = buttons.iter().any(|(_, i)| *i == Interaction::Pressed);
-
-    if !enter && !clicked {
-        return;
+    for event in events.read() {
+        let MenuEvent::Activated { item, .. } = event else {
+            continue;
+        };
+        let Ok(action) = buttons.get(*item) else {
+            continue;
+        };
+        match action {
+            MenuAction::StartGame => {
+                next_state.set(GameState::LevelSelect);
+            }
+            MenuAction::Profiles => {
+                next_state.set(GameState::ProfileSelect);
+            }
+            MenuAction::Statistics => {
+                next_state.set(GameState::Statistics);
+            }
+            MenuAction::Settings => {
+                *settings_origin = SettingsOrigin::Menu;
+                next_state.set(GameState::Settings);
+            }
+            MenuAction::Exit => {
+                dialogs.write(OpenDialog::confirm(EXIT_DIALOG, locale.get("menu.exit_title"), "", &locale));
+            }
+        }
     }
+}
 
-    match selected.0 {
-        0 => {
-            next_state.set(GameState::LevelSelect);
-        }
-        1 => {
-            *settings_origin = SettingsOrigin::Menu;
-            next_state.set(GameState::Settings);
-        }
-        2 => {
+fn menu_exit_confirm(mut answers: MessageReader<DialogClosed>, mut exit_events: MessageWriter<AppExit>) {
+    for answer in answers.read() {
+        if answer.confirmed(EXIT_DIALOG) {
             exit_events.write(AppExit::default());
         }
-        _ => {}
     }
 }
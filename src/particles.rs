@@ -0,0 +1,405 @@
+use bevy::prelude::*;
+
+use crate::accessibility::WorldColors;
+use crate::level::{Exit, LevelCompleted};
+use crate::player::{Player, PlayerDied, PlayerJumped, PlayerLanded};
+use crate::settings::GameSettings;
+use crate::states::GameState;
+
+/// Sprites shared by all effects; when they run out the oldest particle is reused.
+const POOL_SIZE: usize = 256;
+/// Every level starts from this seed, so the same run always throws the same particles.
+const PARTICLE_SEED: u64 = 0x5EED_1234_ABCD_0001;
+/// In front of the level tiles.
+const PARTICLE_Z: f32 = 5.0;
+/// From the player's centre to its feet.
+const FEET_OFFSET: Vec2 = Vec2::new(0.0, -16.0);
+
+/// Small xorshift generator, enough for particle spread and reproducible from a seed.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct ParticleRng(u64);
+
+impl ParticleRng {
+    pub fn new(seed: u64) -> Self {
+        // Zero would make xorshift return zeros forever
+        Self(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform in `[min, max)`.
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        let unit = (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+        min + (max - min) * unit
+    }
+}
+
+impl Default for ParticleRng {
+    fn default() -> Self {
+        Self::new(PARTICLE_SEED)
+    }
+}
+
+/// Gameplay moments with a particle effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleEffect {
+    Jump,
+    Land,
+    Death,
+    Exit,
+}
+
+/// Emitter preset: how many particles a burst has and how they move and fade.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Burst {
+    pub count: usize,
+    /// Direction in degrees, 90 is straight up.
+    pub angle: f32,
+    /// Degrees either side of `angle`.
+    pub spread: f32,
+    pub speed: (f32, f32),
+    pub lifetime: (f32, f32),
+    /// Downward acceleration in pixels per second squared.
+    pub gravity: f32,
+    pub size: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+}
+
+impl ParticleEffect {
+    pub fn burst(self, colors: &WorldColors) -> Burst {
+        let dust = Color::srgba(0.85, 0.82, 0.75, 0.9);
+        match self {
+            ParticleEffect::Jump => Burst {
+                count: 6,
+                angle: -90.0,
+                spread: 60.0,
+                speed: (40.0, 90.0),
+                lifetime: (0.2, 0.35),
+                gravity: 0.0,
+                size: 3.0,
+                start_color: dust,
+                end_color: dust.with_alpha(0.0),
+            },
+            ParticleEffect::Land => Burst {
+                count: 10,
+                angle: 90.0,
+                spread: 80.0,
+                speed: (40.0, 110.0),
+                lifetime: (0.25, 0.4),
+                gravity: 400.0,
+                size: 3.0,
+                start_color: dust,
+                end_color: dust.with_alpha(0.0),
+            },
+            ParticleEffect::Death => Burst {
+                count: 24,
+                angle: 90.0,
+                spread: 180.0,
+                speed: (80.0, 220.0),
+                lifetime: (0.4, 0.7),
+                gravity: 600.0,
+                size: 4.0,
+                start_color: colors.player,
+                end_color: colors.spikes.with_alpha(0.0),
+            },
+            ParticleEffect::Exit => Burst {
+                count: 32,
+                angle: 90.0,
+                spread: 50.0,
+                speed: (60.0, 200.0),
+                lifetime: (0.5, 0.9),
+                gravity: -80.0,
+                size: 3.0,
+                start_color: Color::WHITE,
+                end_color: colors.exit.with_alpha(0.0),
+            },
+        }
+    }
+}
+
+/// Plays `effect` at `position`. Any system can write this message.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct SpawnParticles {
+    pub effect: ParticleEffect,
+    pub position: Vec2,
+}
+
+/// Motion and look of one live particle, kept apart from its sprite so it can be stepped in tests.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParticleState {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub gravity: f32,
+    pub age: f32,
+    pub lifetime: f32,
+    pub start_color: Color,
+    pub end_color: Color,
+}
+
+impl ParticleState {
+    /// Moves the particle on by `delta` seconds; false once it has lived out its lifetime.
+    pub fn step(&mut self, delta: f32) -> bool {
+        self.age += delta;
+        self.velocity.y -= self.gravity * delta;
+        self.position += self.velocity * delta;
+        self.age < self.lifetime
+    }
+
+    /// Color fading from start to end over the particle's life.
+    pub fn color(&self) -> Color {
+        let t = (self.age / self.lifetime).clamp(0.0, 1.0);
+        self.start_color.mix(&self.end_color, t)
+    }
+}
+
+/// Particles of one burst at `position`, all drawn from `rng`.
+pub fn emit(burst: &Burst, position: Vec2, rng: &mut ParticleRng) -> Vec<ParticleState> {
+    (0..burst.count)
+        .map(|_| {
+            let angle = (burst.angle + rng.range(-burst.spread, burst.spread)).to_radians();
+            let speed = rng.range(burst.speed.0, burst.speed.1);
+            ParticleState {
+                position,
+                velocity: Vec2::from_angle(angle) * speed,
+                gravity: burst.gravity,
+                age: 0.0,
+                lifetime: rng.range(burst.lifetime.0, burst.lifetime.1),
+                start_color: burst.start_color,
+                end_color: burst.end_color,
+            }
+        })
+        .collect()
+}
+
+/// A pooled particle sprite; `None` while it's free.
+#[derive(Component, Debug, Default)]
+struct Particle(Option<ParticleState>);
+
+/// The pooled sprites, handed out round-robin.
+#[derive(Resource, Debug, Default)]
+struct ParticlePool {
+    entities: Vec<Entity>,
+    next: usize,
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticleRng>()
+            .init_resource::<ParticlePool>()
+            .add_message::<SpawnParticles>()
+            .add_systems(Startup, spawn_pool)
+            .add_systems(OnEnter(GameState::Playing), reset_rng)
+            .add_systems(OnExit(GameState::Playing), clear_particles)
+            .add_systems(
+                Update,
+                (emit_gameplay_particles, spawn_particles, update_particles)
+                    .chain()
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(particles_enabled),
+            );
+    }
+}
+
+/// Reduced motion turns particles off.
+fn particles_enabled(settings: Res<GameSettings>) -> bool {
+    !settings.reduced_motion
+}
+
+fn spawn_pool(mut commands: Commands, mut pool: ResMut<ParticlePool>) {
+    pool.entities = (0..POOL_SIZE)
+        .map(|_| {
+            commands
+                .spawn((
+                    Particle::default(),
+                    Sprite::default(),
+                    Transform::from_xyz(0.0, 0.0, PARTICLE_Z),
+                    Visibility::Hidden,
+                ))
+                .id()
+        })
+        .collect();
+}
+
+fn reset_rng(mut rng: ResMut<ParticleRng>) {
+    *rng = ParticleRng::default();
+}
+
+fn emit_gameplay_particles(
+    mut jumped: MessageReader<PlayerJumped>,
+    mut landed: MessageReader<PlayerLanded>,
+    mut died: MessageReader<PlayerDied>,
+    mut completed: MessageReader<LevelCompleted>,
+    player_query: Query<&Transform, With<Player>>,
+    exit_query: Query<&Transform, With<Exit>>,
+    mut spawn: MessageWriter<SpawnParticles>,
+) {
+    let feet = player_query
+        .single()
+        .map(|transform| transform.translation.truncate() + FEET_OFFSET)
+        .ok();
+    if let Some(position) = feet {
+        for _ in jumped.read() {
+            spawn.write(SpawnParticles {
+                effect: ParticleEffect::Jump,
+                position,
+            });
+        }
+        for _ in landed.read() {
+            spawn.write(SpawnParticles {
+                effect: ParticleEffect::Land,
+                position,
+            });
+        }
+    }
+    for death in died.read() {
+        spawn.write(SpawnParticles {
+            effect: ParticleEffect::Death,
+            position: death.position,
+        });
+    }
+    if completed.read().count() > 0 {
+        for exit in &exit_query {
+            spawn.write(SpawnParticles {
+                effect: ParticleEffect::Exit,
+                position: exit.translation.truncate(),
+            });
+        }
+    }
+}
+
+fn spawn_particles(
+    mut requests: MessageReader<SpawnParticles>,
+    settings: Res<GameSettings>,
+    mut rng: ResMut<ParticleRng>,
+    mut pool: ResMut<ParticlePool>,
+    mut particles: Query<(&mut Particle, &mut Sprite)>,
+) {
+    let colors = settings.palette.colors();
+    for request in requests.read() {
+        let burst = request.effect.burst(&colors);
+        for state in emit(&burst, request.position, &mut rng) {
+            if pool.entities.is_empty() {
+                return;
+            }
+            let entity = pool.entities[pool.next];
+            pool.next = (pool.next + 1) % pool.entities.len();
+            let Ok((mut particle, mut sprite)) = particles.get_mut(entity) else {
+                continue;
+            };
+            particle.0 = Some(state);
+            sprite.custom_size = Some(Vec2::splat(burst.size));
+        }
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut particles: Query<(&mut Particle, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    for (mut particle, mut sprite, mut transform, mut visibility) in &mut particles {
+        let Some(state) = particle.0.as_mut() else {
+            continue;
+        };
+        if !state.step(time.delta_secs()) {
+            particle.0 = None;
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        sprite.color = state.color();
+        transform.translation = state.position.extend(PARTICLE_Z);
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn clear_particles(mut particles: Query<(&mut Particle, &mut Visibility)>) {
+    for (mut particle, mut visibility) in &mut particles {
+        particle.0 = None;
+        *visibility = Visibility::Hidden;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accessibility::Palette;
+
+    #[test]
+    fn same_seed_same_particles() {
+        let burst = ParticleEffect::Death.burst(&Palette::Standard.colors());
+        let first = emit(&burst, Vec2::new(10.0, 20.0), &mut ParticleRng::new(42));
+        let second = emit(&burst, Vec2::new(10.0, 20.0), &mut ParticleRng::new(42));
+        assert_eq!(first, second);
+        assert_eq!(first.len(), burst.count);
+
+        let other = emit(&burst, Vec2::new(10.0, 20.0), &mut ParticleRng::new(43));
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn bursts_stay_within_their_preset() {
+        let mut rng = ParticleRng::default();
+        let colors = Palette::Standard.colors();
+        for effect in [ParticleEffect::Jump, ParticleEffect::Land, ParticleEffect::Death, ParticleEffect::Exit] {
+            let burst = effect.burst(&colors);
+            for particle in emit(&burst, Vec2::ZERO, &mut rng) {
+                let speed = particle.velocity.length();
+                assert!(speed >= burst.speed.0 - 1e-3 && speed <= burst.speed.1 + 1e-3, "{:?}", effect);
+                assert!(particle.lifetime >= burst.lifetime.0 && particle.lifetime <= burst.lifetime.1);
+            }
+        }
+    }
+
+    #[test]
+    fn particles_fall_fade_and_expire() {
+        let mut particle = ParticleState {
+            position: Vec2::ZERO,
+            velocity: Vec2::new(100.0, 0.0),
+            gravity: 200.0,
+            age: 0.0,
+            lifetime: 0.5,
+            start_color: Color::WHITE,
+            end_color: Color::WHITE.with_alpha(0.0),
+        };
+        assert!(particle.step(0.25));
+        assert_eq!(particle.position.x, 25.0);
+        assert!(particle.velocity.y < 0.0 && particle.position.y < 0.0);
+        assert!((particle.color().alpha() - 0.5).abs() < 1e-3);
+        assert!(!particle.step(0.25));
+    }
+
+    #[test]
+    fn pool_reuses_the_oldest_sprite() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<GameSettings>();
+        app.init_resource::<ParticleRng>();
+        app.init_resource::<ParticlePool>();
+        app.add_message::<SpawnParticles>();
+        app.add_systems(Startup, spawn_pool);
+        app.add_systems(Update, spawn_particles);
+        app.update();
+
+        // Exit bursts are 32 particles, so nine of them wrap around the pool
+        for _ in 0..9 {
+            app.world_mut().write_message(SpawnParticles {
+                effect: ParticleEffect::Exit,
+                position: Vec2::ZERO,
+            });
+        }
+        app.update();
+
+        let pool = app.world().resource::<ParticlePool>();
+        assert_eq!(pool.entities.len(), POOL_SIZE);
+        assert_eq!(pool.next, (9 * 32) % POOL_SIZE);
+        let mut live = app.world_mut().query::<&Particle>();
+        assert!(live.iter(app.world()).all(|particle| particle.0.is_some()));
+    }
+}
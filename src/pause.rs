@@ -1,59 +1,67 @@
 use bevy::prelude::*;
 
+use crate::actions::{Action, Actions};
+use crate::fade::fading_out;
+use crate::fonts::UiFonts;
+use crate::level::{CustomLevel, SkipLevel};
+use crate::locale::Locale;
 use crate::player::DeathCounter;
+use crate::settings::GameSettings;
 use crate::states::{GameState, SettingsOrigin};
+use crate::widgets::{spawn_menu, DialogClosed, MenuEntry, MenuEvent, MenuStyle, MenuSystems, Modal, OpenDialog};
 
-/// Tracks which pause menu button is currently selected (0-indexed).
-#[derive(Resource, Debug, Clone, Default)]
-pub struct SelectedPauseItem(pub usize);
+const QUIT_DIALOG: &str = "quit_to_menu";
 
 /// Component attached to each pause button to identify its action.
 #[derive(Component, Debug, Clone, PartialEq, Eq)]
 pub enum PauseAction {
     Resume,
+    /// Only offered with the level skip assist on.
+    SkipLevel,
     Settings,
     ToMenu,
 }
 
-const PAUSE_ITEMS: usize = 3;
-const COLOR_SELECTED: Color = Color::srgb(0.3, 0.3, 0.7);
-const COLOR_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
-
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SelectedPauseItem>()
-            .add_systems(Update, pause_toggle)
+        app.add_systems(
+            Update,
+            pause_toggle.before(MenuSystems::Navigate).run_if(not(fading_out)),
+        )
             .add_systems(OnEnter(GameState::Paused), spawn_pause_overlay)
             .add_systems(
                 Update,
-                (pause_navigation, pause_mouse, pause_highlight, pause_action)
-                    .chain()
+                (pause_action, pause_quit_confirm)
+                    .in_set(MenuSystems::Respond)
                     .run_if(in_state(GameState::Paused)),
             );
     }
 }
 
 fn pause_toggle(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     state: Res<State<GameState>>,
     mut next_state: ResMut<NextState<GameState>>,
+    modals: Query<(), With<Modal>>,
 ) {
-    if !keyboard.just_pressed(KeyCode::Escape) {
-        return;
-    }
-
+    // Back closes an open dialog instead of resuming
+    let resume = modals.is_empty() && (actions.just_pressed(Action::Pause) || actions.just_pressed(Action::Back));
     match state.get() {
-        GameState::Playing => next_state.set(GameState::Paused),
-        GameState::Paused => next_state.set(GameState::Playing),
+        GameState::Playing if actions.just_pressed(Action::Pause) => next_state.set(GameState::Paused),
+        GameState::Paused if resume => next_state.set(GameState::Playing),
         _ => {}
     }
 }
 
-fn spawn_pause_overlay(mut commands: Commands, mut selected: ResMut<SelectedPauseItem>) {
-    selected.0 = 0;
-
+fn spawn_pause_overlay(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    custom: Res<CustomLevel>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
     commands
         .spawn((
             // Full-screen semi-transparent overlay
@@ -82,11 +90,8 @@ fn spawn_pause_overlay(mut commands: Commands, mut selected: ResMut<SelectedPaus
                 .with_children(|panel| {
                     // Title
                     panel.spawn((
-                        Text::new("PAUSED"),
-                        TextFont {
-                            font_size: 40.0,
-                            ..default()
-                        },
+                        Text::new(locale.get("pause.title")),
+                        fonts.title(40.0),
                         TextColor(Color::WHITE),
                         Node {
                             margin: UiRect::bottom(Val::Px(40.0)),
@@ -95,115 +100,65 @@ fn spawn_pause_overlay(mut commands: Commands, mut selected: ResMut<SelectedPaus
                     ));
 
                     // Buttons
-                    let buttons = [
-                        ("Resume", PauseAction::Resume),
-                        ("Settings", PauseAction::Settings),
-                        ("Main Menu", PauseAction::ToMenu),
-                    ];
-
-                    for (label, action) in buttons {
-                        panel
-                            .spawn((
-                                Button,
-                                action,
-                                Node {
-                                    width: Val::Px(300.0),
-                                    height: Val::Px(50.0),
-                                    justify_content: JustifyContent::Center,
-                                    align_items: AlignItems::Center,
-                                    margin: UiRect::vertical(Val::Px(5.0)),
-                                    ..default()
-                                },
-                                BackgroundColor(COLOR_NORMAL),
-                            ))
-                            .with_children(|btn| {
-                                btn.spawn((
-                                    Text::new(label),
-                                    TextFont {
-                                        font_size: 24.0,
-                                        ..default()
-                                    },
-                                    TextColor(Color::WHITE),
-                                ));
-                            });
+                    let mut buttons = vec![MenuEntry::button(PauseAction::Resume, locale.get("pause.resume"))];
+                    if settings.level_skip && custom.0.is_none() {
+                        buttons.push(MenuEntry::button(PauseAction::SkipLevel, locale.get("pause.skip_level")));
                     }
+                    buttons.push(MenuEntry::button(PauseAction::Settings, locale.get("pause.settings")));
+                    buttons.push(MenuEntry::button(PauseAction::ToMenu, locale.get("pause.main_menu")));
+                    spawn_menu(panel, &fonts, MenuStyle::default(), buttons);
                 });
         });
 }
 
-fn pause_navigation(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut selected: ResMut<SelectedPauseItem>,
+fn pause_action(
+    mut events: MessageReader<MenuEvent>,
+    buttons: Query<&PauseAction>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut settings_origin: ResMut<SettingsOrigin>,
+    mut dialogs: MessageWriter<OpenDialog>,
+    mut skips: MessageWriter<SkipLevel>,
+    locale: Res<Locale>,
 ) {
-    if keyboard.just_pressed(KeyCode::ArrowUp) {
-        selected.0 = if selected.0 == 0 {
-            PAUSE_ITEMS - 1
-        } else {
-            selected.0 - 1
+    for event in events.read() {
+        let MenuEvent::Activated { item, .. } = event else {
+            continue;
         };
-    }
-    if keyboard.just_pressed(KeyCode::ArrowDown) {
-        selected.0 = (selected.0 + 1) % PAUSE_ITEMS;
-    }
-}
-
-fn pause_mouse(
-    mut selected: ResMut<SelectedPauseItem>,
-    buttons: Query<(&PauseAction, &Interaction), Changed<Interaction>>,
-) {
-    let actions_order = [PauseAction::Resume, PauseAction::Settings, PauseAction::ToMenu];
-
-    for (action, interaction) in &buttons {
-        if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
-            let index = actions_order.iter().position(|a| a == action).unwrap_or(0);
-            selected.0 = index;
-        }
-    }
-}
-
-fn pause_highlight(
-    selected: Res<SelectedPauseItem>,
-    mut buttons: Query<(&PauseAction, &mut BackgroundColor)>,
-) {
-    let actions_order = [PauseAction::Resume, PauseAction::Settings, PauseAction::ToMenu];
-
-    for (action, mut bg) in &mut buttons {
-        let index = actions_order.iter().position(|a| a == action).unwrap_or(0);
-        if index == selected.0 {
-            *bg = BackgroundColor(COLOR_SELECTED);
-        } else {
-            *bg = BackgroundColor(COLOR_NORMAL);
+        let Ok(action) = buttons.get(*item) else {
+            continue;
+        };
+        match action {
+            PauseAction::Resume => {
+                next_state.set(GameState::Playing);
+            }
+            PauseAction::SkipLevel => {
+                skips.write(SkipLevel);
+            }
+            PauseAction::Settings => {
+                *settings_origin = SettingsOrigin::Paused;
+                next_state.set(GameState::Settings);
+            }
+            PauseAction::ToMenu => {
+                dialogs.write(OpenDialog::confirm(
+                    QUIT_DIALOG,
+                    locale.get("pause.quit_title"),
+                    locale.get("pause.quit_message"),
+                    &locale,
+                ));
+            }
         }
     }
 }
 
-fn pause_action(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    selected: Res<SelectedPauseItem>,
+fn pause_quit_confirm(
+    mut answers: MessageReader<DialogClosed>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut settings_origin: ResMut<SettingsOrigin>,
-    mut commands: Commands,
-    buttons: Query<(&PauseAction, &Interaction)>,
+    mut counter: ResMut<DeathCounter>,
 ) {
-    let enter = keyboard.just_pressed(KeyCode::Enter);
-    let clicked = buttons.iter().any(|(_, i)| *i == Interaction::Pressed);
-
-    if !enter && !clicked {
-        return;
-    }
-
-    match selected.0 {
-        0 => {
-            next_state.set(GameState::Playing);
-        }
-        1 => {
-            *settings_origin = SettingsOrigin::Paused;
-            next_state.set(GameState::Settings);
-        }
-        2 => {
-            commands.remove_resource::<DeathCounter>();
+    for answer in answers.read() {
+        if answer.confirmed(QUIT_DIALOG) {
+            *counter = DeathCounter::default();
             next_state.set(GameState::Menu);
         }
-        _ => {}
     }
 }
@@ -1,8 +1,14 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
 
+use crate::accessibility::GAME_SPEEDS;
+use crate::fade::fading_out;
+use crate::settings::GameSettings;
 use crate::states::GameState;
 
+/// FixedUpdate ticks per second at full speed, Bevy's default.
+const FIXED_HZ: f64 = 64.0;
+
 pub struct PhysicsPlugin;
 
 impl Plugin for PhysicsPlugin {
@@ -10,7 +16,25 @@ impl Plugin for PhysicsPlugin {
         app.add_plugins(avian2d::PhysicsPlugins::default())
             .insert_resource(Gravity(Vec2::new(0.0, -980.0)))
             .add_systems(OnEnter(GameState::Playing), unpause_physics)
-            .add_systems(OnExit(GameState::Playing), pause_physics);
+            .add_systems(OnExit(GameState::Playing), pause_physics)
+            // Freeze the level while it fades out after the exit
+            .add_systems(Update, pause_physics.run_if(fading_out))
+            .add_systems(Update, apply_game_speed.run_if(resource_changed::<GameSettings>));
+    }
+}
+
+/// Slows only the gameplay clock: FixedUpdate ticks less often and `Time<Physics>` scales
+/// each tick back to a full-speed step, so there is still one physics step of the same
+/// size per tick and ghosts and replays stay in sync. Menus, fades and music keep real speed.
+fn apply_game_speed(
+    settings: Res<GameSettings>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    let speed = settings.game_speed.clamp(GAME_SPEEDS[0], 1.0) as f64;
+    if physics_time.relative_speed_f64() != speed {
+        fixed_time.set_timestep_hz(FIXED_HZ * speed);
+        physics_time.set_relative_speed_f64(speed);
     }
 }
 
@@ -21,3 +45,44 @@ fn pause_physics(mut physics_time: ResMut<Time<Physics>>) {
 fn unpause_physics(mut physics_time: ResMut<Time<Physics>>) {
     physics_time.unpause();
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::fade::{spawn_screen_fade, FadePlugin, ScreenFade};
+
+    #[test]
+    fn slow_game_speed_leaves_menus_and_fades_at_real_speed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, FadePlugin));
+        app.init_state::<GameState>();
+        app.init_resource::<Time<Physics>>();
+        app.insert_resource(GameSettings {
+            game_speed: 0.5,
+            ..default()
+        });
+        app.add_systems(Update, apply_game_speed.run_if(resource_changed::<GameSettings>));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app.update();
+
+        let mut commands = app.world_mut().commands();
+        spawn_screen_fade(&mut commands, ScreenFade::to_black(0.25, GameState::Menu), GameState::ProfileSelect);
+        app.world_mut().flush();
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(*app.world().resource::<State<GameState>>().get(), GameState::Menu);
+        assert_eq!(app.world().resource::<Time<Virtual>>().relative_speed(), 1.0);
+
+        // Half as many ticks, each still a full-speed physics step
+        let fixed = app.world().resource::<Time<Fixed>>().timestep();
+        let physics = app.world().resource::<Time<Physics>>();
+        assert_eq!(fixed, Duration::from_secs_f64(2.0 / FIXED_HZ));
+        assert_eq!(fixed.mul_f64(physics.relative_speed_f64()), Duration::from_secs_f64(1.0 / FIXED_HZ));
+    }
+}
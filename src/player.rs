@@ -1,6 +1,10 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::actions::{Action, Actions};
+use crate::animation::{PlayerAnimation, PlayerSheet};
+use crate::settings::GameSettings;
 use crate::states::GameState;
 
 #[derive(Component)]
@@ -12,9 +16,49 @@ pub struct Grounded(pub bool);
 #[derive(Component)]
 pub struct GroundSensor;
 
+/// Mid-air jumps used since the player last stood on the ground.
+#[derive(Component, Default)]
+pub struct AirJumps(pub u32);
+
+/// Where the player last stood; an invincible player falling off the level comes back here.
+#[derive(Component, Default)]
+pub struct LastGround(pub Vec2);
+
 #[derive(Resource, Default)]
 pub struct SpawnPoint(pub Vec2);
 
+/// What the player wants to do this fixed tick, independent of where it came from
+/// (keyboard, replay, tests).
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlayerInput {
+    pub left: bool,
+    pub right: bool,
+    pub jump: bool,
+}
+
+/// Fixed-tick ordering: everything that fills `PlayerInput` runs between
+/// `Input` and `Movement`.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayerSystems {
+    Input,
+    Movement,
+}
+
+/// Written whenever the player dies and is sent back to the spawn point.
+#[derive(Message, Debug, Clone, Copy, Default)]
+pub struct PlayerDied {
+    /// Where the player died, before the respawn moved it.
+    pub position: Vec2,
+}
+
+/// Written on the tick the player touches the ground after being in the air.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct PlayerLanded;
+
+/// Written on the tick the player jumps, from the ground or in mid-air.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct PlayerJumped;
+
 #[derive(Resource, Default)]
 pub struct DeathCounter {
     pub current_level: usize,
@@ -27,11 +71,26 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<SpawnPoint>()
             .init_resource::<DeathCounter>()
+            .init_resource::<PlayerInput>()
+            .add_message::<PlayerDied>()
+            .add_message::<PlayerJumped>()
+            .add_message::<PlayerLanded>()
+            .configure_sets(
+                FixedUpdate,
+                (PlayerSystems::Input, PlayerSystems::Movement).chain(),
+            )
             .add_systems(OnEnter(GameState::Playing), (spawn_player, reset_level_deaths))
+            .add_systems(
+                FixedUpdate,
+                read_keyboard_input
+                    .in_set(PlayerSystems::Input)
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(
                 FixedUpdate,
                 (ground_detection, player_movement)
                     .chain()
+                    .in_set(PlayerSystems::Movement)
                     .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
@@ -41,16 +100,30 @@ impl Plugin for PlayerPlugin {
     }
 }
 
-fn spawn_player(mut commands: Commands, spawn_point: Res<SpawnPoint>) {
+fn spawn_player(
+    mut commands: Commands,
+    spawn_point: Res<SpawnPoint>,
+    settings: Res<GameSettings>,
+    sheet: Option<Res<PlayerSheet>>,
+) {
+    let size = Vec2::new(24.0, 32.0);
+    let color = settings.palette.colors().player;
+    let sprite = match sheet {
+        Some(sheet) => sheet.sprite(size, color),
+        None => Sprite {
+            color,
+            custom_size: Some(size),
+            ..default()
+        },
+    };
     commands
         .spawn((
             Player,
             Grounded(false),
-            Sprite {
-                color: Color::srgb(0.2, 0.4, 0.9),
-                custom_size: Some(Vec2::new(24.0, 32.0)),
-                ..default()
-            },
+            AirJumps::default(),
+            LastGround(spawn_point.0),
+            PlayerAnimation::default(),
+            sprite,
             RigidBody::Dynamic,
             Collider::rectangle(24.0, 32.0),
             LockedAxes::ROTATION_LOCKED,
@@ -70,33 +143,56 @@ fn spawn_player(mut commands: Commands, spawn_point: Res<SpawnPoint>) {
 
 fn ground_detection(
     sensor_query: Query<(&CollidingEntities, &ChildOf), With<GroundSensor>>,
-    mut player_query: Query<&mut Grounded, With<Player>>,
+    mut player_query: Query<(&mut Grounded, &mut LastGround, &Transform), With<Player>>,
+    mut landed: MessageWriter<PlayerLanded>,
 ) {
     for (colliding, child_of) in &sensor_query {
-        if let Ok(mut grounded) = player_query.get_mut(child_of.parent()) {
+        if let Ok((mut grounded, mut last_ground, transform)) = player_query.get_mut(child_of.parent()) {
+            let was_grounded = grounded.0;
             grounded.0 = !colliding.is_empty();
+            if grounded.0 {
+                last_ground.0 = transform.translation.truncate();
+            }
+            if grounded.0 && !was_grounded {
+                landed.write(PlayerLanded);
+            }
         }
     }
 }
 
+fn read_keyboard_input(actions: Actions, mut input: ResMut<PlayerInput>) {
+    *input = PlayerInput {
+        left: actions.pressed(Action::MoveLeft),
+        right: actions.pressed(Action::MoveRight),
+        jump: actions.just_pressed(Action::Jump),
+    };
+}
+
 fn player_movement(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut LinearVelocity, &Grounded), With<Player>>,
+    input: Res<PlayerInput>,
+    settings: Res<GameSettings>,
+    mut query: Query<(&mut LinearVelocity, &Grounded, &mut AirJumps), With<Player>>,
+    mut jumped: MessageWriter<PlayerJumped>,
 ) {
-    for (mut velocity, grounded) in &mut query {
-        let left = keyboard.pressed(KeyCode::KeyA) || keyboard.pressed(KeyCode::ArrowLeft);
-        let right = keyboard.pressed(KeyCode::KeyD) || keyboard.pressed(KeyCode::ArrowRight);
-
-        if left && !right {
+    for (mut velocity, grounded, mut air_jumps) in &mut query {
+        if input.left && !input.right {
             velocity.x = -300.0;
-        } else if right && !left {
+        } else if input.right && !input.left {
             velocity.x = 300.0;
         } else {
             velocity.x = 0.0;
         }
 
-        if keyboard.just_pressed(KeyCode::Space) && grounded.0 {
+        if grounded.0 {
+            air_jumps.0 = 0;
+        }
+        let can_jump = grounded.0 || air_jumps.0 < settings.air_jumps;
+        if input.jump && can_jump {
+            if !grounded.0 {
+                air_jumps.0 += 1;
+            }
             velocity.y = 500.0;
+            jumped.write(PlayerJumped);
         }
     }
 }
@@ -106,15 +202,23 @@ fn reset_level_deaths(mut counter: ResMut<DeathCounter>) {
 }
 
 fn player_death(
-    mut query: Query<(&mut Transform, &mut LinearVelocity), With<Player>>,
+    mut query: Query<(&mut Transform, &mut LinearVelocity, &LastGround), With<Player>>,
     spawn_point: Res<SpawnPoint>,
+    settings: Res<GameSettings>,
     mut counter: ResMut<DeathCounter>,
+    mut died: MessageWriter<PlayerDied>,
 ) {
-    for (mut transform, mut velocity) in &mut query {
-        if transform.translation.y < -500.0 {
+    for (mut transform, mut velocity, last_ground) in &mut query {
+        if transform.translation.y < -500.0 && settings.invincible {
+            transform.translation = last_ground.0.extend(0.0);
+            *velocity = LinearVelocity::ZERO;
+            debug!("Invincible fall, back to {:?}", last_ground.0);
+        } else if transform.translation.y < -500.0 {
+            let position = transform.translation.truncate();
             transform.translation = spawn_point.0.extend(0.0);
             *velocity = LinearVelocity::ZERO;
             counter.current_level += 1;
+            died.write(PlayerDied { position });
             debug!("Death by falling (level deaths: {})", counter.current_level);
         }
     }
@@ -130,12 +234,15 @@ mod tests {
         app.add_plugins(MinimalPlugins);
         app.init_resource::<SpawnPoint>();
         app.init_resource::<DeathCounter>();
+        app.init_resource::<GameSettings>();
+        app.add_message::<PlayerDied>();
         app.add_systems(Update, player_death);
 
         app.world_mut().spawn((
             Player,
             Transform::from_xyz(0.0, -600.0, 0.0),
             LinearVelocity::ZERO,
+            LastGround::default(),
         ));
 
         app.update();
@@ -144,18 +251,84 @@ mod tests {
         assert_eq!(counter.current_level, 1);
     }
 
+    #[test]
+    fn invincible_fall_returns_to_last_ground() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<SpawnPoint>();
+        app.init_resource::<DeathCounter>();
+        app.insert_resource(GameSettings {
+            invincible: true,
+            ..default()
+        });
+        app.add_message::<PlayerDied>();
+        app.add_systems(Update, player_death);
+
+        let player = app
+            .world_mut()
+            .spawn((
+                Player,
+                Transform::from_xyz(0.0, -600.0, 0.0),
+                LinearVelocity(Vec2::new(0.0, -800.0)),
+                LastGround(Vec2::new(320.0, 64.0)),
+            ))
+            .id();
+
+        app.update();
+
+        assert_eq!(app.world().resource::<DeathCounter>().current_level, 0);
+        let transform = app.world().entity(player).get::<Transform>().unwrap();
+        assert_eq!(transform.translation.truncate(), Vec2::new(320.0, 64.0));
+    }
+
+    #[test]
+    fn air_jumps_limited_by_setting() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.insert_resource(PlayerInput {
+            jump: true,
+            ..default()
+        });
+        app.insert_resource(GameSettings {
+            air_jumps: 1,
+            ..default()
+        });
+        app.add_message::<PlayerJumped>();
+        app.add_systems(Update, player_movement);
+
+        let player = app
+            .world_mut()
+            .spawn((Player, Grounded(false), AirJumps::default(), LinearVelocity::ZERO))
+            .id();
+
+        app.update();
+        assert_eq!(app.world().entity(player).get::<LinearVelocity>().unwrap().y, 500.0);
+
+        // The only air jump is used up until the player lands
+        app.world_mut().entity_mut(player).insert(LinearVelocity::ZERO);
+        app.update();
+        assert_eq!(app.world().entity(player).get::<LinearVelocity>().unwrap().y, 0.0);
+
+        app.world_mut().entity_mut(player).insert(Grounded(true));
+        app.update();
+        assert_eq!(app.world().entity(player).get::<AirJumps>().unwrap().0, 0);
+    }
+
     #[test]
     fn no_death_above_threshold() {
         let mut app = App::new();
         app.add_plugins(MinimalPlugins);
         app.init_resource::<SpawnPoint>();
         app.init_resource::<DeathCounter>();
+        app.init_resource::<GameSettings>();
+        app.add_message::<PlayerDied>();
         app.add_systems(Update, player_death);
 
         app.world_mut().spawn((
             Player,
             Transform::from_xyz(0.0, 100.0, 0.0),
             LinearVelocity::ZERO,
+            LastGround::default(),
         ));
 
         app.update();
@@ -170,12 +343,15 @@ mod tests {
         app.add_plugins(MinimalPlugins);
         app.insert_resource(SpawnPoint(Vec2::new(100.0, 200.0)));
         app.init_resource::<DeathCounter>();
+        app.init_resource::<GameSettings>();
+        app.add_message::<PlayerDied>();
         app.add_systems(Update, player_death);
 
         let entity = app.world_mut().spawn((
             Player,
             Transform::from_xyz(0.0, -600.0, 0.0),
             LinearVelocity(Vec2::new(300.0, -400.0)),
+            LastGround::default(),
         )).id();
 
         app.update();
@@ -0,0 +1,685 @@
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{load_bindings, Action, Actions, KeyBindings};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::progress::{data_dir, load_progress, set_profile_dir, PlayerProgress};
+use crate::settings::{load_profile_settings, GameSettings};
+use crate::states::GameState;
+use crate::storage;
+use crate::widgets::{
+    spawn_menu, DialogClosed, Menu, MenuEntry, MenuEvent, MenuLayout, MenuStyle, MenuSystems, OpenDialog,
+    COLOR_DANGER, COLOR_NORMAL,
+};
+
+const DEFAULT_PROFILE: &str = "Default";
+const MAX_NAME_LEN: usize = 16;
+/// Columns of a profile row: select, rename, delete.
+const PROFILE_COLUMNS: usize = 3;
+
+/// Files and folders that lived directly in the data directory before profiles existed.
+const LEGACY_ENTRIES: &[&str] = &[
+    "save.json",
+    "save.json.bak",
+    "save.json.1",
+    "save.json.2",
+    "save.json.3",
+    "replays",
+    "ghosts",
+];
+
+/// Name of the profile whose data is loaded.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ActiveProfile(pub String);
+
+/// Remembers the last used profile between launches.
+#[derive(Serialize, Deserialize, Default)]
+struct ProfileIndex {
+    last_used: Option<String>,
+}
+
+#[derive(Resource, Default)]
+struct ProfileList(Vec<String>);
+
+#[derive(Resource, Default)]
+enum ProfileDialog {
+    #[default]
+    None,
+    /// Typing a name for a new profile (`original: None`) or a rename.
+    /// `error` is a locale key.
+    Naming {
+        original: Option<String>,
+        text: String,
+        error: Option<&'static str>,
+    },
+    /// Waiting for the answer to the delete confirmation.
+    ConfirmDelete { name: String },
+}
+
+/// Cell of the grid: `row` is a profile (or the "New Profile" row), `col` a row button.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+struct ProfileButton {
+    row: usize,
+    col: usize,
+}
+
+/// Marker for the name input overlay root.
+#[derive(Component)]
+struct ProfileOverlay;
+
+#[derive(Component)]
+struct NameInputText;
+
+const DELETE_DIALOG: &str = "delete_profile";
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let root = profiles_root(&data_dir());
+        match migrate_legacy_save(&data_dir(), &root) {
+            Ok(true) => info!("Moved existing save into profile '{}'", DEFAULT_PROFILE),
+            Ok(false) => {}
+            Err(e) => error!("Failed to migrate existing save: {}", e),
+        }
+
+        app.init_resource::<ActiveProfile>()
+            .init_resource::<ProfileList>()
+            .init_resource::<ProfileDialog>()
+            .add_systems(OnEnter(GameState::ProfileSelect), setup_profile_select)
+            .add_systems(
+                Update,
+                (profile_text_input, profile_confirm, profile_action)
+                    .chain()
+                    .in_set(MenuSystems::Respond)
+                    .run_if(in_state(GameState::ProfileSelect)),
+            );
+    }
+}
+
+fn profiles_root(data: &Path) -> PathBuf {
+    data.join("profiles")
+}
+
+fn index_path() -> PathBuf {
+    data_dir().join("profiles.json")
+}
+
+fn load_index() -> ProfileIndex {
+    std::fs::read_to_string(index_path())
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &ProfileIndex) {
+    match serde_json::to_string_pretty(index) {
+        Ok(json) => {
+            if let Err(e) = storage::write_atomic(&index_path(), json.as_bytes()) {
+                error!("Failed to save profile index: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize profile index: {}", e),
+    }
+}
+
+/// Profile names, sorted. Each profile is a directory under `root`.
+fn list_profiles(root: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .collect();
+    names.sort_by_key(|n| n.to_lowercase());
+    names
+}
+
+/// Names double as directory names, so only allow characters safe on every platform.
+/// Errors are locale keys.
+fn validate_name(name: &str, existing: &[String]) -> Result<(), &'static str> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("profiles.error.empty");
+    }
+    if trimmed.chars().count() > MAX_NAME_LEN {
+        return Err("profiles.error.too_long");
+    }
+    if !trimmed
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_')
+    {
+        return Err("profiles.error.characters");
+    }
+    if existing.iter().any(|n| n.to_lowercase() == trimmed.to_lowercase()) {
+        return Err("profiles.error.exists");
+    }
+    Ok(())
+}
+
+fn create_profile(root: &Path, name: &str) -> io::Result<()> {
+    std::fs::create_dir_all(root.join(name))
+}
+
+fn rename_profile(root: &Path, old: &str, new: &str) -> io::Result<()> {
+    std::fs::rename(root.join(old), root.join(new))
+}
+
+fn delete_profile(root: &Path, name: &str) -> io::Result<()> {
+    std::fs::remove_dir_all(root.join(name))
+}
+
+/// Moves a pre-profile save from the data directory into the default profile.
+/// Returns true if anything was moved.
+fn migrate_legacy_save(data: &Path, root: &Path) -> io::Result<bool> {
+    if !data.join("save.json").exists() || root.exists() {
+        return Ok(false);
+    }
+    let target = root.join(DEFAULT_PROFILE);
+    std::fs::create_dir_all(&target)?;
+    for entry in LEGACY_ENTRIES {
+        let from = data.join(entry);
+        if from.exists() {
+            std::fs::rename(&from, target.join(entry))?;
+        }
+    }
+    Ok(true)
+}
+
+fn refresh_profiles(root: &Path) -> Vec<String> {
+    let mut names = list_profiles(root);
+    if names.is_empty() {
+        if let Err(e) = create_profile(root, DEFAULT_PROFILE) {
+            error!("Failed to create default profile: {}", e);
+        }
+        names = list_profiles(root);
+    }
+    names
+}
+
+/// Existing profile matching `name` (ignoring case), or a newly created one.
+fn find_or_create_profile(root: &Path, name: &str) -> Result<String, &'static str> {
+    let existing = list_profiles(root);
+    let name = name.trim();
+    if let Some(found) = existing.iter().find(|n| n.to_lowercase() == name.to_lowercase()) {
+        return Ok(found.clone());
+    }
+    validate_name(name, &existing)?;
+    create_profile(root, name).map_err(|_| "profiles.error.create")?;
+    Ok(name.to_string())
+}
+
+/// Profile to use when the picker is skipped: `requested`, else the last used one.
+pub fn launch_profile(requested: Option<&str>) -> Result<String, &'static str> {
+    let root = profiles_root(&data_dir());
+    if let Some(name) = requested {
+        return find_or_create_profile(&root, name);
+    }
+    let existing = refresh_profiles(&root);
+    let last_used = load_index().last_used.filter(|n| existing.contains(n));
+    Ok(last_used
+        .or_else(|| existing.first().cloned())
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string()))
+}
+
+/// Everything kept per profile. Loaded all at once, so the screen entered next is
+/// built from the profile's settings, keys and language rather than the previous ones.
+#[derive(SystemParam)]
+pub struct ProfileData<'w> {
+    pub active: ResMut<'w, ActiveProfile>,
+    pub progress: ResMut<'w, PlayerProgress>,
+    settings: ResMut<'w, GameSettings>,
+    bindings: ResMut<'w, KeyBindings>,
+    pub locale: ResMut<'w, Locale>,
+}
+
+impl ProfileData<'_> {
+    /// Points saving at the profile's directory and loads its data.
+    pub fn activate(&mut self, name: &str) {
+        set_profile_dir(profiles_root(&data_dir()).join(name));
+        *self.progress = load_progress();
+        self.load_settings();
+        self.active.0 = name.to_string();
+        save_index(&ProfileIndex {
+            last_used: Some(name.to_string()),
+        });
+        info!("Profile '{}' selected", name);
+    }
+
+    /// Nothing is loaded until another profile is picked. Settings and keys fall back
+    /// to the shared ones, since the profile's files are gone.
+    fn unload(&mut self) {
+        self.active.0.clear();
+        *self.progress = PlayerProgress::default();
+        self.load_settings();
+    }
+
+    fn load_settings(&mut self) {
+        let settings = load_profile_settings(&self.settings);
+        if *self.settings != settings {
+            *self.settings = settings;
+        }
+        let bindings = load_bindings();
+        if *self.bindings != bindings {
+            *self.bindings = bindings;
+        }
+        if self.locale.language != self.settings.language {
+            *self.locale = Locale::load(self.settings.language);
+        }
+    }
+}
+
+fn setup_profile_select(
+    mut commands: Commands,
+    mut list: ResMut<ProfileList>,
+    mut dialog: ResMut<ProfileDialog>,
+    active: Res<ActiveProfile>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
+    list.0 = refresh_profiles(&profiles_root(&data_dir()));
+    *dialog = ProfileDialog::None;
+
+    let preferred = if active.0.is_empty() {
+        load_index().last_used
+    } else {
+        Some(active.0.clone())
+    };
+    let preferred_row = preferred
+        .and_then(|name| list.0.iter().position(|n| *n == name))
+        .unwrap_or(0);
+
+    // One row per profile: name, Rename, Delete. "New Profile" goes alone on the last row.
+    let mut entries = Vec::new();
+    for (row, name) in list.0.iter().enumerate() {
+        let mut select = MenuEntry::button(ProfileButton { row, col: 0 }, name.as_str());
+        if row == preferred_row {
+            select = select.selected();
+        }
+        entries.push(select);
+        entries.push(MenuEntry::button(ProfileButton { row, col: 1 }, locale.get("profiles.rename")).width(120.0));
+        entries.push(
+            MenuEntry::button(ProfileButton { row, col: 2 }, locale.get("profiles.delete"))
+                .width(120.0)
+                .colors(COLOR_NORMAL, COLOR_DANGER),
+        );
+    }
+    let new_profile = ProfileButton {
+        row: list.0.len(),
+        col: 0,
+    };
+    entries.push(MenuEntry::button(new_profile, locale.get("profiles.new")).gap_before(10.0));
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DespawnOnExit::<GameState>(GameState::ProfileSelect),
+        ))
+        .with_children(|parent| {
+            // Title
+            parent.spawn((
+                Text::new(locale.get("profiles.title")),
+                fonts.title(40.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(30.0)),
+                    ..default()
+                },
+            ));
+
+            let style = MenuStyle {
+                layout: MenuLayout::Grid {
+                    columns: PROFILE_COLUMNS,
+                },
+                ..default()
+            };
+            spawn_menu(parent, &fonts, style, entries);
+        });
+}
+
+fn spawn_name_overlay(commands: &mut Commands, fonts: &UiFonts, title: &str, locale: &Locale) {
+    commands
+        .spawn((
+            ProfileOverlay,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            GlobalZIndex(200),
+            DespawnOnExit::<GameState>(GameState::ProfileSelect),
+        ))
+        .with_children(|overlay| {
+            overlay
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(40.0)),
+                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.12, 0.12, 0.12)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text::new(title),
+                        fonts.title(32.0),
+                        TextColor(Color::WHITE),
+                        Node { margin: UiRect::bottom(Val::Px(20.0)), ..default() },
+                    ));
+                    panel.spawn((
+                        NameInputText,
+                        Text::new("_"),
+                        fonts.text(28.0),
+                        TextColor(Color::WHITE),
+                        Node { margin: UiRect::bottom(Val::Px(20.0)), ..default() },
+                    ));
+                    panel.spawn((
+                        Text::new(locale.get("profiles.name_hint")),
+                        fonts.text(18.0),
+                        TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                    ));
+                    // Takes menu focus, so typing doesn't move the selection behind the overlay
+                    panel.spawn(Menu::default());
+                });
+        });
+}
+
+fn close_overlay(commands: &mut Commands, overlay_query: &Query<Entity, With<ProfileOverlay>>) {
+    for entity in overlay_query {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn profile_text_input(
+    mut commands: Commands,
+    mut key_events: MessageReader<KeyboardInput>,
+    actions: Actions,
+    mut dialog: ResMut<ProfileDialog>,
+    list: Res<ProfileList>,
+    mut active: ResMut<ActiveProfile>,
+    mut next_state: ResMut<NextState<GameState>>,
+    overlay_query: Query<Entity, With<ProfileOverlay>>,
+    mut input_text: Query<&mut Text, With<NameInputText>>,
+    locale: Res<Locale>,
+) {
+    // Always drain, so keys pressed before the dialog opened don't leak into it
+    let typed: Vec<KeyboardInput> = key_events.read().cloned().collect();
+
+    let ProfileDialog::Naming { original, text, error } = &mut *dialog else {
+        return;
+    };
+
+    if actions.just_pressed(Action::Back) {
+        close_overlay(&mut commands, &overlay_query);
+        *dialog = ProfileDialog::None;
+        return;
+    }
+
+    for event in typed.iter().filter(|e| e.state.is_pressed()) {
+        if event.key_code == KeyCode::Backspace {
+            text.pop();
+            continue;
+        }
+        if let Some(chars) = &event.text {
+            for c in chars.chars().filter(|c| !c.is_control()) {
+                if text.chars().count() < MAX_NAME_LEN {
+                    text.push(c);
+                }
+            }
+        }
+    }
+
+    if actions.just_pressed(Action::Confirm) {
+        let name = text.trim().to_string();
+        // Renaming to the same name with different case is allowed
+        let others: Vec<String> = list
+            .0
+            .iter()
+            .filter(|n| Some(*n) != original.as_ref())
+            .cloned()
+            .collect();
+        match validate_name(&name, &others) {
+            Ok(()) => {
+                let root = profiles_root(&data_dir());
+                let result = match original {
+                    Some(old) => rename_profile(&root, old, &name),
+                    None => create_profile(&root, &name),
+                };
+                match result {
+                    Ok(()) => {
+                        // Keep saving into the renamed folder
+                        if original.as_ref() == Some(&active.0) {
+                            set_profile_dir(root.join(&name));
+                            active.0 = name.clone();
+                            save_index(&ProfileIndex {
+                                last_used: Some(name),
+                            });
+                        }
+                        // Re-entering the state rebuilds the list
+                        next_state.set(GameState::ProfileSelect);
+                        return;
+                    }
+                    Err(e) => {
+                        error!("Failed to save profile '{}': {}", name, e);
+                        *error = Some("profiles.error.save");
+                    }
+                }
+            }
+            Err(e) => *error = Some(e),
+        }
+    }
+
+    for mut t in &mut input_text {
+        **t = match error {
+            Some(e) => format!("{}_\n{}", text, locale.get(e)),
+            None => format!("{}_", text),
+        };
+    }
+}
+
+fn profile_confirm(
+    mut answers: MessageReader<DialogClosed>,
+    mut dialog: ResMut<ProfileDialog>,
+    mut profile: ProfileData,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for answer in answers.read() {
+        if answer.id != DELETE_DIALOG {
+            continue;
+        }
+        let ProfileDialog::ConfirmDelete { name } = &*dialog else {
+            continue;
+        };
+        if answer.confirmed(DELETE_DIALOG) {
+            match delete_profile(&profiles_root(&data_dir()), name) {
+                Ok(()) => info!("Profile '{}' deleted", name),
+                Err(e) => error!("Failed to delete profile '{}': {}", name, e),
+            }
+            if *name == profile.active.0 {
+                profile.unload();
+            }
+            next_state.set(GameState::ProfileSelect);
+        }
+        *dialog = ProfileDialog::None;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn profile_action(
+    mut commands: Commands,
+    mut events: MessageReader<MenuEvent>,
+    mut dialogs: MessageWriter<OpenDialog>,
+    list: Res<ProfileList>,
+    mut dialog: ResMut<ProfileDialog>,
+    mut profile: ProfileData,
+    mut next_state: ResMut<NextState<GameState>>,
+    buttons: Query<&ProfileButton>,
+    fonts: Res<UiFonts>,
+) {
+    // Always drain, so events from the name overlay aren't seen after it closes
+    let events: Vec<MenuEvent> = events.read().copied().collect();
+
+    // A dialog closed this frame already consumed the key press
+    if !matches!(*dialog, ProfileDialog::None) || dialog.is_changed() {
+        return;
+    }
+
+    let mut pressed = None;
+    for event in events {
+        match event {
+            // Back to the menu only if a profile is already loaded
+            MenuEvent::Back { .. } if !profile.active.0.is_empty() => {
+                next_state.set(GameState::Menu);
+                return;
+            }
+            MenuEvent::Activated { item, .. } => pressed = buttons.get(item).ok().copied(),
+            _ => {}
+        }
+    }
+    let Some(selected) = pressed else {
+        return;
+    };
+
+    let locale = &profile.locale;
+    let Some(name) = list.0.get(selected.row) else {
+        // "New Profile" row
+        *dialog = ProfileDialog::Naming {
+            original: None,
+            text: String::new(),
+            error: None,
+        };
+        spawn_name_overlay(&mut commands, &fonts, locale.get("profiles.new_title"), locale);
+        return;
+    };
+
+    match selected.col {
+        0 => {
+            profile.activate(name);
+            next_state.set(GameState::Menu);
+        }
+        1 => {
+            *dialog = ProfileDialog::Naming {
+                original: Some(name.clone()),
+                text: name.clone(),
+                error: None,
+            };
+            spawn_name_overlay(&mut commands, &fonts, locale.get("profiles.rename_title"), locale);
+        }
+        2 => {
+            *dialog = ProfileDialog::ConfirmDelete { name: name.clone() };
+            dialogs.write(OpenDialog::confirm(
+                DELETE_DIALOG,
+                locale.format("profiles.delete_title", &[("name", name)]),
+                locale.get("profiles.delete_message"),
+                locale,
+            ));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_validation() {
+        let existing = vec!["Alice".to_string()];
+        assert!(validate_name("Bob", &existing).is_ok());
+        assert!(validate_name("Миша", &existing).is_ok());
+        assert!(validate_name("my-profile_2", &existing).is_ok());
+        assert!(validate_name("   ", &existing).is_err());
+        assert!(validate_name("alice", &existing).is_err());
+        assert!(validate_name("../evil", &existing).is_err());
+        assert!(validate_name("a very long profile name", &existing).is_err());
+
+        // Errors are shown through the locale
+        let locale = Locale::default();
+        for name in ["   ", "alice", "../evil", "a very long profile name"] {
+            let key = validate_name(name, &existing).unwrap_err();
+            assert_ne!(locale.get(key), key, "{} missing from en.ron", key);
+        }
+    }
+
+    #[test]
+    fn create_rename_delete() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = profiles_root(dir.path());
+
+        create_profile(&root, "Bob").unwrap();
+        create_profile(&root, "alice").unwrap();
+        assert_eq!(list_profiles(&root), vec!["alice", "Bob"]);
+
+        rename_profile(&root, "Bob", "Robert").unwrap();
+        assert_eq!(list_profiles(&root), vec!["alice", "Robert"]);
+
+        delete_profile(&root, "alice").unwrap();
+        assert_eq!(list_profiles(&root), vec!["Robert"]);
+    }
+
+    #[test]
+    fn empty_root_gets_default_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = profiles_root(dir.path());
+
+        assert_eq!(refresh_profiles(&root), vec![DEFAULT_PROFILE]);
+    }
+
+    #[test]
+    fn find_or_create_reuses_existing_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = profiles_root(dir.path());
+        create_profile(&root, "Alice").unwrap();
+
+        assert_eq!(find_or_create_profile(&root, "alice"), Ok("Alice".to_string()));
+        assert_eq!(find_or_create_profile(&root, " Bob "), Ok("Bob".to_string()));
+        assert!(find_or_create_profile(&root, "a/b").is_err());
+        assert_eq!(list_profiles(&root), vec!["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn legacy_save_moves_into_default_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = profiles_root(dir.path());
+        std::fs::write(dir.path().join("save.json"), r#"{"max_unlocked_level": 3}"#).unwrap();
+        std::fs::write(dir.path().join("save.json.1"), r#"{"max_unlocked_level": 2}"#).unwrap();
+        std::fs::create_dir_all(dir.path().join("ghosts")).unwrap();
+        std::fs::write(dir.path().join("game.log"), "log").unwrap();
+
+        assert!(migrate_legacy_save(dir.path(), &root).unwrap());
+
+        let profile = root.join(DEFAULT_PROFILE);
+        assert!(profile.join("save.json").exists());
+        assert!(profile.join("save.json.1").exists());
+        assert!(profile.join("ghosts").is_dir());
+        assert!(!dir.path().join("save.json").exists());
+        // Not profile data
+        assert!(dir.path().join("game.log").exists());
+
+        // Runs only once
+        assert!(!migrate_legacy_save(dir.path(), &root).unwrap());
+    }
+}
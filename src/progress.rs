@@ -1,32 +1,171 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 
-#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+use crate::storage;
+
+/// How many previous saves are kept as `save.json.1`, `save.json.2`, ...
+const SAVE_BACKUPS: usize = 3;
+
+/// Current save schema. Bump together with a new entry in `MIGRATIONS`.
+pub const SAVE_VERSION: u32 = 1;
+
+/// `MIGRATIONS[n]` upgrades a save from version `n` to `n + 1`.
+const MIGRATIONS: &[fn(Value) -> Value] = &[migrate_v0_to_v1];
+
+#[derive(Resource, Serialize, Deserialize, Clone)]
 pub struct PlayerProgress {
+    /// Save schema version; files without it are version 0.
+    pub version: u32,
     pub max_unlocked_level: usize,
+    /// Personal best per level in seconds, indexed like `LEVELS`.
+    #[serde(default)]
+    pub best_level_times: Vec<Option<f32>>,
+    /// Personal best for a full run from level 1 to the end.
+    #[serde(default)]
+    pub best_run: Option<BestRun>,
+    /// Lifetime statistics per level, indexed like `LEVELS`.
+    #[serde(default)]
+    pub level_stats: Vec<LevelStats>,
+}
+
+impl Default for PlayerProgress {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            max_unlocked_level: 0,
+            best_level_times: Vec::new(),
+            best_run: None,
+            level_stats: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BestRun {
+    pub time: f32,
+    pub splits: Vec<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct LevelStats {
+    pub attempts: u32,
+    pub completions: u32,
+    /// Completions and skips with an assist on; not part of `completions`.
+    pub assisted_completions: u32,
+    pub deaths: u32,
+    /// Seconds spent in the level, pauses excluded.
+    pub play_time: f32,
+}
+
+impl PlayerProgress {
+    pub fn level_stats(&self, level: usize) -> LevelStats {
+        self.level_stats.get(level).cloned().unwrap_or_default()
+    }
+
+    pub fn level_stats_mut(&mut self, level: usize) -> &mut LevelStats {
+        if self.level_stats.len() <= level {
+            self.level_stats.resize(level + 1, LevelStats::default());
+        }
+        &mut self.level_stats[level]
+    }
+
+    pub fn best_level_time(&self, level: usize) -> Option<f32> {
+        self.best_level_times.get(level).copied().flatten()
+    }
+
+    /// Stores `time` if it beats the current best. Returns true on a new record.
+    pub fn record_level_time(&mut self, level: usize, time: f32) -> bool {
+        if self.best_level_time(level).is_some_and(|best| best <= time) {
+            return false;
+        }
+        if self.best_level_times.len() <= level {
+            self.best_level_times.resize(level + 1, None);
+        }
+        self.best_level_times[level] = Some(time);
+        true
+    }
+
+    /// Stores a full run if it beats the current best. Returns true on a new record.
+    pub fn record_run(&mut self, time: f32, splits: &[f32]) -> bool {
+        if self.best_run.as_ref().is_some_and(|best| best.time <= time) {
+            return false;
+        }
+        self.best_run = Some(BestRun {
+            time,
+            splits: splits.to_vec(),
+        });
+        true
+    }
 }
 
 pub struct ProgressPlugin;
 
 impl Plugin for ProgressPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(load_progress());
+        // The real progress is loaded once a profile is picked
+        app.init_resource::<PlayerProgress>();
     }
 }
 
-fn save_path() -> PathBuf {
-    let base = dirs::data_dir()
-        .unwrap_or_else(|| PathBuf::from("."));
-    let dir = base.join("simple_platformer");
+/// Directory of the selected profile, set by the profile picker.
+static PROFILE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+static DATA_DIR_OVERRIDE: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Directory for the save file and other per-user data.
+pub fn data_dir() -> PathBuf {
+    let overridden = DATA_DIR_OVERRIDE.read().ok().and_then(|dir| dir.clone());
+    let dir = overridden.unwrap_or_else(|| {
+        let base = dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."));
+        base.join("simple_platformer")
+    });
+    std::fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Replaces the platform data directory, e.g. from `--data-dir`. Call before any plugin is built.
+pub fn set_data_dir(dir: PathBuf) {
+    if let Ok(mut current) = DATA_DIR_OVERRIDE.write() {
+        *current = Some(dir);
+    }
+}
+
+/// Directory for the active profile's save, replays and ghosts.
+/// Falls back to `data_dir()` until a profile is selected.
+pub fn profile_dir() -> PathBuf {
+    PROFILE_DIR
+        .read()
+        .ok()
+        .and_then(|dir| dir.clone())
+        .unwrap_or_else(data_dir)
+}
+
+pub fn set_profile_dir(dir: PathBuf) {
     std::fs::create_dir_all(&dir).ok();
-    dir.join("save.json")
+    if let Ok(mut current) = PROFILE_DIR.write() {
+        *current = Some(dir);
+    }
 }
 
-fn save_progress_to(progress: &PlayerProgress, path: &std::path::Path) {
+fn save_path() -> PathBuf {
+    profile_dir().join("save.json")
+}
+
+fn save_progress_to(progress: &PlayerProgress, path: &Path) {
+    if std::fs::read_to_string(path).is_ok_and(|contents| is_newer_save(&contents)) {
+        error!("Not saving progress: {:?} is from a newer version of the game", path);
+        return;
+    }
     match serde_json::to_string_pretty(progress) {
         Ok(json) => {
-            if let Err(e) = std::fs::write(path, json) {
+            if let Err(e) = storage::rotate_backups(path, SAVE_BACKUPS) {
+                warn!("Failed to rotate save backups: {}", e);
+            }
+            if let Err(e) = storage::write_atomic(path, json.as_bytes()) {
                 error!("Failed to save progress: {}", e);
             } else {
                 info!("Progress saved (max_level: {})", progress.max_unlocked_level);
@@ -36,26 +175,111 @@ fn save_progress_to(progress: &PlayerProgress, path: &std::path::Path) {
     }
 }
 
-fn load_progress_from(path: &std::path::Path) -> PlayerProgress {
-    match std::fs::read_to_string(path) {
-        Ok(s) => match serde_json::from_str(&s) {
-            Ok(p) => {
-                info!("Progress loaded from {:?}", path);
-                p
-            }
-            Err(e) => {
-                error!("Failed to parse progress: {}", e);
-                PlayerProgress::default()
-            }
-        },
+/// Version 0 is the original `{"max_unlocked_level": N}` save.
+fn migrate_v0_to_v1(mut value: Value) -> Value {
+    if let Some(obj) = value.as_object_mut() {
+        obj.entry("best_level_times").or_insert_with(|| json!([]));
+        obj.entry("best_run").or_insert(Value::Null);
+        obj.entry("level_stats").or_insert_with(|| json!([]));
+        obj.insert("version".to_string(), json!(1));
+    }
+    value
+}
+
+fn save_version(value: &Value) -> Result<u32, String> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| format!("invalid version field: {}", v)),
+    }
+}
+
+/// Written by a newer build. Such a save isn't corrupt, so it is neither moved aside
+/// nor saved over; going back to that build finds it as it was.
+fn is_newer_save(contents: &str) -> bool {
+    serde_json::from_str::<Value>(contents)
+        .ok()
+        .and_then(|value| save_version(&value).ok())
+        .is_some_and(|version| version > SAVE_VERSION)
+}
+
+/// Parses a save of any known version and upgrades it to `SAVE_VERSION`.
+fn parse_progress(contents: &str) -> Result<PlayerProgress, String> {
+    let mut value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    if !value.is_object() {
+        return Err("save is not a JSON object".to_string());
+    }
+
+    let version = save_version(&value)?;
+    if version > SAVE_VERSION {
+        return Err(format!(
+            "save version {} is newer than supported version {}",
+            version, SAVE_VERSION
+        ));
+    }
+
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        info!("Migrating save from version {} to {}", from, from + 1);
+        value = migrate(value);
+    }
+
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+fn load_progress_from(path: &Path) -> PlayerProgress {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(s) => s,
         Err(_) => {
             info!("No save file found, starting fresh");
-            PlayerProgress::default()
+            return PlayerProgress::default();
+        }
+    };
+
+    if is_newer_save(&contents) {
+        error!(
+            "{:?} is from a newer version of the game; playing without it, and progress won't be saved",
+            path
+        );
+        return PlayerProgress::default();
+    }
+
+    match parse_progress(&contents) {
+        Ok(p) => {
+            info!("Progress loaded from {:?}", path);
+            p
+        }
+        Err(e) => {
+            // Move the unreadable file out of the way: left in place, the next save would
+            // rotate it into the backups and push out the oldest good one
+            let backup = storage::with_suffix(path, ".bak");
+            error!("Failed to parse progress: {}. Moving it to {:?}", e, backup);
+            if let Err(e) = std::fs::rename(path, &backup) {
+                error!("Failed to move save file aside: {}", e);
+            }
+            load_newest_backup(path).unwrap_or_default()
         }
     }
 }
 
-fn load_progress() -> PlayerProgress {
+fn load_newest_backup(path: &Path) -> Option<PlayerProgress> {
+    for backup in storage::backup_paths(path, SAVE_BACKUPS) {
+        let Ok(contents) = std::fs::read_to_string(&backup) else {
+            continue;
+        };
+        match parse_progress(&contents) {
+            Ok(p) => {
+                warn!("Progress restored from backup {:?}", backup);
+                return Some(p);
+            }
+            Err(e) => warn!("Backup {:?} is unreadable too: {}", backup, e),
+        }
+    }
+    None
+}
+
+pub fn load_progress() -> PlayerProgress {
     load_progress_from(&save_path())
 }
 
@@ -72,12 +296,65 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let path = dir.path().join("test_save.json");
 
-        let progress = PlayerProgress { max_unlocked_level: 3 };
+        let progress = PlayerProgress { max_unlocked_level: 3, ..Default::default() };
         save_progress_to(&progress, &path);
         let loaded = load_progress_from(&path);
         assert_eq!(loaded.max_unlocked_level, 3);
     }
 
+    #[test]
+    fn old_save_without_records_still_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("old_save.json");
+
+        std::fs::write(&path, r#"{"max_unlocked_level": 2}"#).unwrap();
+        let loaded = load_progress_from(&path);
+        assert_eq!(loaded.max_unlocked_level, 2);
+        assert!(loaded.best_level_times.is_empty());
+        assert!(loaded.best_run.is_none());
+        assert!(loaded.level_stats.is_empty());
+        assert_eq!(loaded.level_stats(0), LevelStats::default());
+    }
+
+    #[test]
+    fn level_stats_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats_save.json");
+
+        let mut progress = PlayerProgress::default();
+        let stats = progress.level_stats_mut(2);
+        stats.attempts = 4;
+        stats.deaths = 7;
+        stats.completions = 1;
+        stats.play_time = 95.5;
+        save_progress_to(&progress, &path);
+
+        let loaded = load_progress_from(&path);
+        assert_eq!(loaded.level_stats.len(), 3);
+        assert_eq!(loaded.level_stats(0), LevelStats::default());
+        assert_eq!(loaded.level_stats(2), progress.level_stats(2));
+    }
+
+    #[test]
+    fn level_record_only_kept_when_faster() {
+        let mut progress = PlayerProgress::default();
+        assert!(progress.record_level_time(2, 30.0));
+        assert!(!progress.record_level_time(2, 31.0));
+        assert!(progress.record_level_time(2, 29.5));
+        assert_eq!(progress.best_level_time(2), Some(29.5));
+        assert_eq!(progress.best_level_time(0), None);
+    }
+
+    #[test]
+    fn run_record_keeps_splits() {
+        let mut progress = PlayerProgress::default();
+        assert!(progress.record_run(100.0, &[40.0, 60.0]));
+        assert!(!progress.record_run(120.0, &[50.0, 70.0]));
+        let best = progress.best_run.unwrap();
+        assert_eq!(best.time, 100.0);
+        assert_eq!(best.splits, vec![40.0, 60.0]);
+    }
+
     #[test]
     fn load_missing_file_returns_default() {
         let dir = tempfile::tempdir().unwrap();
@@ -96,4 +373,100 @@ mod tests {
         let loaded = load_progress_from(&path);
         assert_eq!(loaded.max_unlocked_level, 0);
     }
+
+    #[test]
+    fn corrupted_save_backed_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        std::fs::write(&path, "not valid json {{{{").unwrap();
+        load_progress_from(&path);
+        let backup = std::fs::read_to_string(dir.path().join("save.json.bak")).unwrap();
+        assert_eq!(backup, "not valid json {{{{");
+    }
+
+    #[test]
+    fn upgrade_v0_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        std::fs::write(&path, r#"{"max_unlocked_level": 4}"#).unwrap();
+        let loaded = load_progress_from(&path);
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.max_unlocked_level, 4);
+        assert!(loaded.best_level_times.is_empty());
+        assert!(loaded.best_run.is_none());
+        assert!(loaded.level_stats.is_empty());
+        assert!(!dir.path().join("save.json.bak").exists());
+
+        // Saving writes the upgraded schema back
+        save_progress_to(&loaded, &path);
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], json!(SAVE_VERSION));
+        assert_eq!(saved["max_unlocked_level"], json!(4));
+    }
+
+    #[test]
+    fn newer_save_version_left_alone() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        let future = format!(r#"{{"version": {}, "max_unlocked_level": 2}}"#, SAVE_VERSION + 1);
+        std::fs::write(&path, &future).unwrap();
+        let loaded = load_progress_from(&path);
+        assert_eq!(loaded.max_unlocked_level, 0);
+        assert!(!dir.path().join("save.json.bak").exists());
+
+        // Neither overwritten nor rotated into the backups
+        save_progress_to(&loaded, &path);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), future);
+        assert!(storage::backup_paths(&path, SAVE_BACKUPS).iter().all(|p| !p.exists()));
+    }
+
+    #[test]
+    fn corrupted_save_falls_back_to_newest_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        for level in 1..=3 {
+            let progress = PlayerProgress { max_unlocked_level: level, ..Default::default() };
+            save_progress_to(&progress, &path);
+        }
+        // Simulate a truncated write on the main file
+        std::fs::write(&path, r#"{"version": 1, "max_unl"#).unwrap();
+
+        let loaded = load_progress_from(&path);
+        assert_eq!(loaded.max_unlocked_level, 2);
+        assert!(!path.exists());
+
+        // The next save rotates only readable saves into the backups
+        save_progress_to(&loaded, &path);
+        for backup in storage::backup_paths(&path, SAVE_BACKUPS) {
+            if let Ok(contents) = std::fs::read_to_string(&backup) {
+                assert!(parse_progress(&contents).is_ok(), "{:?} is unreadable", backup);
+            }
+        }
+        let oldest = storage::backup_paths(&path, SAVE_BACKUPS).remove(1);
+        let contents = std::fs::read_to_string(oldest).unwrap();
+        assert_eq!(parse_progress(&contents).unwrap().max_unlocked_level, 1);
+    }
+
+    #[test]
+    fn save_keeps_previous_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        save_progress_to(&PlayerProgress { max_unlocked_level: 1, ..Default::default() }, &path);
+        save_progress_to(&PlayerProgress { max_unlocked_level: 2, ..Default::default() }, &path);
+
+        let backup = storage::backup_paths(&path, SAVE_BACKUPS).remove(0);
+        let contents = std::fs::read_to_string(backup).unwrap();
+        assert_eq!(parse_progress(&contents).unwrap().max_unlocked_level, 1);
+        assert_eq!(load_progress_from(&path).max_unlocked_level, 2);
+    }
+
+    #[test]
+    fn migration_chain_covers_every_version() {
+        assert_eq!(MIGRATIONS.len(), SAVE_VERSION as usize);
+    }
 }
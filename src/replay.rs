@@ -0,0 +1,490 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{Action, Actions};
+use crate::fade::fading_out;
+use crate::level::{check_exit, playing_bundled_level, CurrentLevel, CustomLevel, LevelCompleted};
+use crate::player::{PlayerInput, PlayerSystems};
+use crate::progress::profile_dir;
+use crate::states::GameState;
+use crate::storage;
+
+/// Bump when the replay layout changes; older files are still accepted.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// A recorded level attempt: one `PlayerInput` per FixedUpdate tick.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Replay {
+    pub version: u32,
+    pub level: usize,
+    pub frames: Vec<PlayerInput>,
+}
+
+/// Input log of the current attempt. Restarted every time a level is entered.
+#[derive(Resource, Default)]
+pub struct InputRecorder {
+    pub frames: Vec<PlayerInput>,
+}
+
+/// While present, recorded input replaces the keyboard on `level`.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub level: usize,
+    pub frames: Vec<PlayerInput>,
+    pub cursor: usize,
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRecorder>()
+            .add_systems(OnEnter(GameState::Playing), (reset_recorder, rewind_playback))
+            // Quitting the level ends a replay, so it can't drive the next level played
+            .add_systems(OnEnter(GameState::Menu), stop_playback)
+            .add_systems(OnEnter(GameState::LevelSelect), stop_playback)
+            .add_systems(
+                FixedUpdate,
+                (replay_input, record_input)
+                    .chain()
+                    .after(PlayerSystems::Input)
+                    .before(PlayerSystems::Movement)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    save_completed_attempt.after(check_exit),
+                    // Replays are stored per bundled level, and restarting mid-fade would cut the transition short
+                    replay_hotkeys.run_if(playing_bundled_level).run_if(not(fading_out)),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn replay_dir() -> PathBuf {
+    let dir = profile_dir().join("replays");
+    std::fs::create_dir_all(&dir).ok();
+    dir
+}
+
+/// Last completed attempt of a level.
+fn replay_path(level: usize) -> PathBuf {
+    replay_dir().join(format!("level_{:02}.json", level + 1))
+}
+
+/// Attempt saved by hand with F8, usually to report a bug.
+fn attempt_path(level: usize) -> PathBuf {
+    replay_dir().join(format!("level_{:02}_attempt.json", level + 1))
+}
+
+pub fn save_replay_to(replay: &Replay, path: &Path) {
+    match serde_json::to_string(replay) {
+        Ok(json) => {
+            if let Err(e) = storage::write_atomic(path, json.as_bytes()) {
+                error!("Failed to save replay: {}", e);
+            } else {
+                info!("Replay saved to {:?} ({} frames)", path, replay.frames.len());
+            }
+        }
+        Err(e) => error!("Failed to serialize replay: {}", e),
+    }
+}
+
+pub fn load_replay_from(path: &Path) -> Option<Replay> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to read replay {:?}: {}", path, e);
+            return None;
+        }
+    };
+    let replay: Replay = match serde_json::from_str(&contents) {
+        Ok(r) => r,
+        Err(e) => {
+            error!("Failed to parse replay {:?}: {}", path, e);
+            return None;
+        }
+    };
+    if replay.version > REPLAY_VERSION {
+        error!(
+            "Replay {:?} has version {}, newest supported is {}",
+            path, replay.version, REPLAY_VERSION
+        );
+        return None;
+    }
+    Some(replay)
+}
+
+fn reset_recorder(mut recorder: ResMut<InputRecorder>) {
+    recorder.frames.clear();
+}
+
+/// Restarts the replay with its level, or drops it once another level is entered,
+/// e.g. after the replayed run reached the exit.
+fn rewind_playback(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    current_level: Res<CurrentLevel>,
+    custom: Res<CustomLevel>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.level == current_level.0 && custom.0.is_none() {
+        playback.cursor = 0;
+    } else {
+        commands.remove_resource::<ReplayPlayback>();
+    }
+}
+
+fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayPlayback>();
+}
+
+fn replay_input(
+    mut commands: Commands,
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    match playback.frames.get(playback.cursor) {
+        Some(frame) => {
+            *input = *frame;
+            playback.cursor += 1;
+        }
+        None => {
+            info!("Replay finished after {} frames", playback.cursor);
+            commands.remove_resource::<ReplayPlayback>();
+        }
+    }
+}
+
+fn record_input(input: Res<PlayerInput>, mut recorder: ResMut<InputRecorder>) {
+    recorder.frames.push(*input);
+}
+
+fn save_completed_attempt(
+    mut completed: MessageReader<LevelCompleted>,
+    recorder: Res<InputRecorder>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    for event in completed.read() {
+        // Replaying a saved run would just write the same file back
+        if playback.is_some() {
+            continue;
+        }
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            level: event.index,
+            frames: recorder.frames.clone(),
+        };
+        save_replay_to(&replay, &replay_path(event.index));
+    }
+}
+
+/// SaveAttempt (F8) saves the attempt in progress, PlayReplay (F9) restarts the level replaying its last completed attempt.
+fn replay_hotkeys(
+    mut commands: Commands,
+    actions: Actions,
+    recorder: Res<InputRecorder>,
+    current_level: Res<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::SaveAttempt) {
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            level: current_level.0,
+            frames: recorder.frames.clone(),
+        };
+        save_replay_to(&replay, &attempt_path(current_level.0));
+    }
+
+    if actions.just_pressed(Action::PlayReplay) {
+        let Some(replay) = load_replay_from(&replay_path(current_level.0)) else {
+            return;
+        };
+        if replay.level != current_level.0 {
+            warn!("Replay is for level {}, not {}", replay.level + 1, current_level.0 + 1);
+            return;
+        }
+        info!("Replaying level {} ({} frames)", replay.level + 1, replay.frames.len());
+        commands.insert_resource(ReplayPlayback {
+            level: replay.level,
+            frames: replay.frames,
+            cursor: 0,
+        });
+        // Passing through LevelTransition reloads the level from the spawn point
+        next_state.set(GameState::LevelTransition);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use avian2d::prelude::*;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::actions::KeyBindings;
+    use crate::assist::LevelAssisted;
+    use crate::fonts::UiFonts;
+    use crate::level::{LevelPlugin, LEVELS};
+    use crate::locale::Locale;
+    use crate::physics::PhysicsPlugin;
+    use crate::player::{Grounded, Player, PlayerPlugin};
+    use crate::progress::PlayerProgress;
+    use crate::settings::GameSettings;
+    use crate::tileset::TilesetPlugin;
+
+    const MAX_TICKS: usize = 64 * 30;
+
+    /// Route through level_01: (jump once x reaches this, hold right until x reaches this).
+    const LEVEL_01_ROUTE: &[(f32, f32)] = &[
+        (170.0, 300.0),
+        (330.0, 416.0),
+        (416.0, 520.0),
+        (520.0, 650.0),
+        (650.0, 790.0),
+    ];
+
+    #[derive(Resource, Default)]
+    struct Bot {
+        step: usize,
+        airborne: bool,
+    }
+
+    /// Closed-loop driver that plays level_01 by looking at the player's position.
+    fn bot_input(
+        mut bot: ResMut<Bot>,
+        mut input: ResMut<PlayerInput>,
+        player: Query<(&Transform, &LinearVelocity, &Grounded), With<Player>>,
+    ) {
+        let Ok((transform, velocity, grounded)) = player.single() else {
+            return;
+        };
+        let x = transform.translation.x;
+        *input = PlayerInput::default();
+
+        let Some(&(jump_x, land_x)) = LEVEL_01_ROUTE.get(bot.step) else {
+            return;
+        };
+
+        if !bot.airborne {
+            if x < jump_x {
+                input.right = true;
+            } else if velocity.y > 100.0 {
+                bot.airborne = true;
+            } else {
+                input.jump = grounded.0;
+            }
+            return;
+        }
+
+        if x < land_x {
+            input.right = true;
+        } else if grounded.0 && velocity.y.abs() < 5.0 {
+            bot.step += 1;
+            bot.airborne = false;
+        }
+    }
+
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            bevy::transform::TransformPlugin,
+            bevy::input::InputPlugin,
+            bevy::asset::AssetPlugin::default(),
+            bevy::image::ImagePlugin::default(),
+            bevy::image::TextureAtlasPlugin,
+            bevy::mesh::MeshPlugin,
+            // avian's collider constructors read the SceneSpawner
+            bevy::scene::ScenePlugin,
+            bevy::state::app::StatesPlugin,
+            PhysicsPlugin,
+            TilesetPlugin,
+            LevelPlugin,
+            PlayerPlugin,
+        ));
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<Locale>();
+        app.init_resource::<UiFonts>();
+        app.init_resource::<GameSettings>();
+        app.init_resource::<LevelAssisted>();
+        // One FixedUpdate tick per app.update()
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 64.0,
+        )));
+        app.insert_state(GameState::Playing);
+        // Everything unlocked, so completing the level doesn't touch the real save file
+        app.insert_resource(PlayerProgress {
+            max_unlocked_level: LEVELS.len(),
+            ..default()
+        });
+        app.init_resource::<InputRecorder>();
+        app.add_systems(OnEnter(GameState::Playing), reset_recorder);
+        app.add_systems(
+            FixedUpdate,
+            (replay_input, record_input)
+                .chain()
+                .after(PlayerSystems::Input)
+                .before(PlayerSystems::Movement),
+        );
+        // Plugins like avian's broad phase set up resources in `finish`, which only `run` calls
+        app.finish();
+        app.cleanup();
+        app
+    }
+
+    /// Runs until level_01 is completed. Returns false on timeout.
+    fn run_until_exit(app: &mut App) -> bool {
+        for _ in 0..MAX_TICKS {
+            app.update();
+            if app.world().resource::<CurrentLevel>().0 == 1 {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn replay_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("replay.json");
+
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            level: 2,
+            frames: vec![
+                PlayerInput { right: true, ..default() },
+                PlayerInput { right: true, jump: true, ..default() },
+            ],
+        };
+        save_replay_to(&replay, &path);
+        assert_eq!(load_replay_from(&path), Some(replay));
+    }
+
+    #[test]
+    fn replay_from_newer_version_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.json");
+
+        let replay = Replay {
+            version: REPLAY_VERSION + 1,
+            level: 0,
+            frames: Vec::new(),
+        };
+        save_replay_to(&replay, &path);
+        assert_eq!(load_replay_from(&path), None);
+    }
+
+    #[test]
+    fn replay_drives_player_input() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<PlayerInput>();
+        app.init_resource::<InputRecorder>();
+        app.insert_resource(ReplayPlayback {
+            level: 0,
+            frames: vec![PlayerInput { left: true, ..default() }],
+            cursor: 0,
+        });
+        app.add_systems(Update, (replay_input, record_input).chain());
+
+        app.update();
+        assert!(app.world().resource::<PlayerInput>().left);
+        assert_eq!(app.world().resource::<InputRecorder>().frames.len(), 1);
+
+        // Out of frames: playback ends and hands control back
+        app.update();
+        assert!(!app.world().contains_resource::<ReplayPlayback>());
+    }
+
+    #[test]
+    fn replay_ends_when_its_level_is_left() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin, ReplayPlugin));
+        app.init_state::<GameState>();
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<PlayerInput>();
+        app.init_resource::<CustomLevel>();
+        app.insert_resource(CurrentLevel(0));
+        app.add_message::<LevelCompleted>();
+        let start_replay = |app: &mut App| {
+            app.insert_resource(ReplayPlayback {
+                level: 0,
+                frames: vec![PlayerInput::default(); 1000],
+                cursor: 0,
+            });
+        };
+        let enter = |app: &mut App, state| {
+            app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+        };
+
+        // Quit mid-replay, then start another level from the menu
+        start_replay(&mut app);
+        enter(&mut app, GameState::Playing);
+        enter(&mut app, GameState::Paused);
+        assert!(app.world().contains_resource::<ReplayPlayback>());
+        enter(&mut app, GameState::Menu);
+        app.insert_resource(CurrentLevel(1));
+        enter(&mut app, GameState::LevelSelect);
+        enter(&mut app, GameState::Playing);
+        assert!(!app.world().contains_resource::<ReplayPlayback>());
+
+        // Going on to the next level after the replayed run
+        app.insert_resource(CurrentLevel(0));
+        start_replay(&mut app);
+        enter(&mut app, GameState::LevelTransition);
+        enter(&mut app, GameState::Playing);
+        assert!(app.world().contains_resource::<ReplayPlayback>());
+        app.insert_resource(CurrentLevel(1));
+        enter(&mut app, GameState::LevelTransition);
+        enter(&mut app, GameState::Playing);
+        assert!(!app.world().contains_resource::<ReplayPlayback>());
+    }
+
+    #[test]
+    fn recorded_level_01_run_replays_to_exit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("level_01.json");
+
+        // Record a run driven by the bot
+        let mut app = headless_app();
+        app.init_resource::<Bot>();
+        app.add_systems(
+            FixedUpdate,
+            bot_input
+                .after(PlayerSystems::Input)
+                .before(replay_input)
+                .run_if(in_state(GameState::Playing)),
+        );
+        assert!(run_until_exit(&mut app), "bot did not reach the exit of level_01");
+
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            level: 0,
+            frames: app.world().resource::<InputRecorder>().frames.clone(),
+        };
+        save_replay_to(&replay, &path);
+
+        // Play it back in a fresh app with nothing but the recording
+        let replay = load_replay_from(&path).expect("recorded replay should load");
+        let mut app = headless_app();
+        app.insert_resource(ReplayPlayback {
+            level: replay.level,
+            frames: replay.frames,
+            cursor: 0,
+        });
+        assert!(run_until_exit(&mut app), "replay did not reach the exit of level_01");
+    }
+}
@@ -1,19 +1,74 @@
-use bevy::prelude::*;
-use bevy::window::{MonitorSelection, WindowMode};
+use std::path::{Path, PathBuf};
 
-use crate::progress::{PlayerProgress, save_progress};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::window::{
+    Monitor, MonitorSelection, PresentMode, PrimaryMonitor, PrimaryWindow, VideoModeSelection, WindowMode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Palette;
+use crate::fonts::UiFonts;
+use crate::locale::{Language, Locale};
+use crate::progress::{PlayerProgress, data_dir, profile_dir, save_progress};
 use crate::states::{GameState, SettingsOrigin};
-
-const SETTINGS_ITEMS: usize = 7;
-const COLOR_SELECTED: Color = Color::srgb(0.3, 0.3, 0.7);
-const COLOR_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
-
-#[derive(Resource)]
+use crate::storage;
+use crate::widgets::{
+    spawn_menu, CloseDialog, DialogClosed, ItemColors, MenuEntry, MenuEvent, MenuItem, MenuStyle, MenuSystems,
+    ModalMessage, OpenDialog, Widget,
+};
+
+/// Offered on every monitor, besides the primary monitor's own modes.
+const RESOLUTIONS: [(u32, u32); 8] = [
+    (1280, 720),
+    (1366, 768),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1080),
+    (2560, 1440),
+    (3440, 1440),
+    (3840, 2160),
+];
+/// `None` is Auto, see `auto_ui_scale`.
+const UI_SCALES: [Option<f32>; 6] = [None, Some(0.75), Some(1.0), Some(1.25), Some(1.5), Some(2.0)];
+/// Seconds to keep new display settings before they are reverted.
+const REVERT_SECONDS: f32 = 15.0;
+const COLOR_UNSAVED: Color = Color::srgb(0.1, 0.4, 0.1);
+const COLOR_UNSAVED_SELECTED: Color = Color::srgb(0.2, 0.8, 0.2);
+
+/// Stored in the profile's `settings.ron`, except for the window settings in the shared
+/// `display.ron`; fields missing from a file keep their defaults.
+#[derive(Resource, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct GameSettings {
     pub music_volume: f32,
     pub sfx_volume: f32,
     pub resolution: (u32, u32),
-    pub fullscreen: bool,
+    pub display_mode: DisplayMode,
+    pub vsync: bool,
+    /// Multiplier for all UI sizes; `None` picks one from the window height.
+    pub ui_scale: Option<f32>,
+    pub show_timer: bool,
+    pub show_ghost: bool,
+    pub language: Language,
+    pub palette: Palette,
+    /// Draws a cross over spikes, for players who can't tell them apart by color.
+    pub hazard_shapes: bool,
+    pub high_contrast: bool,
+    /// Turns off camera shake and smoothing.
+    pub reduced_motion: bool,
+    /// One of `GAME_SPEEDS`; slows gameplay and physics, not menus or fades.
+    pub game_speed: f32,
+    /// Assist: spikes and falls don't kill.
+    pub invincible: bool,
+    /// Assist: jumps allowed in mid-air, `UNLIMITED_AIR_JUMPS` for no limit.
+    pub air_jumps: u32,
+    /// Assist: shows Skip Level in the pause menu.
+    pub level_skip: bool,
+    /// `fullscreen: true` from files written before `display_mode` existed.
+    /// Turned into `display_mode` on load and never written back.
+    #[serde(rename = "fullscreen", skip_serializing)]
+    pub legacy_fullscreen: bool,
 }
 
 impl Default for GameSettings {
@@ -22,7 +77,54 @@ impl Default for GameSettings {
             music_volume: 0.7,
             sfx_volume: 0.7,
             resolution: (1280, 720),
-            fullscreen: false,
+            display_mode: DisplayMode::Windowed,
+            vsync: true,
+            ui_scale: None,
+            show_timer: true,
+            show_ghost: true,
+            language: Language::English,
+            palette: Palette::Standard,
+            hazard_shapes: false,
+            high_contrast: false,
+            reduced_motion: false,
+            game_speed: 1.0,
+            invincible: false,
+            air_jumps: 0,
+            level_skip: false,
+            legacy_fullscreen: false,
+        }
+    }
+}
+
+impl GameSettings {
+    /// Copies the settings that the revert countdown guards.
+    fn copy_display_from(&mut self, other: &GameSettings) {
+        self.resolution = other.resolution;
+        self.display_mode = other.display_mode;
+        self.vsync = other.vsync;
+        self.ui_scale = other.ui_scale;
+    }
+}
+
+/// How the window covers the screen.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// A window covering the monitor, which keeps its own mode.
+    Borderless,
+    /// Switches the monitor to the selected resolution.
+    Exclusive,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [DisplayMode::Windowed, DisplayMode::Borderless, DisplayMode::Exclusive];
+
+    fn label_key(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "settings.windowed",
+            DisplayMode::Borderless => "settings.borderless",
+            DisplayMode::Exclusive => "settings.fullscreen",
         }
     }
 }
@@ -30,23 +132,96 @@ impl Default for GameSettings {
 #[derive(Resource, Default)]
 pub struct SettingsChanged(pub bool);
 
+/// Settings as last saved, restored when unsaved changes are discarded.
 #[derive(Resource, Default)]
-pub struct ConfirmingReset(pub bool);
+struct SavedSettings(GameSettings);
 
+/// Row remembered while Controls is open, so coming back keeps the selection.
 #[derive(Resource, Debug, Clone, Default)]
 pub struct SelectedSettingsItem(pub usize);
 
-/// Marker for each settings row, indexed 0..4.
-#[derive(Component, Debug, Clone)]
-pub struct SettingsRow(pub usize);
+/// Display settings on trial: `previous` comes back unless the player keeps
+/// the new ones before `remaining` runs out.
+#[derive(Resource, Default)]
+struct DisplayRevert {
+    previous: Option<GameSettings>,
+    remaining: f32,
+    row: usize,
+}
 
-/// Marker for the confirmation overlay root entity.
-#[derive(Component)]
-struct ResetOverlay;
+/// Window and monitor the display settings apply to.
+#[derive(SystemParam)]
+struct DisplayTarget<'w, 's> {
+    windows: Query<'w, 's, &'static mut Window, With<PrimaryWindow>>,
+    monitors: Query<'w, 's, &'static Monitor, With<PrimaryMonitor>>,
+}
 
-/// Which confirmation button is selected: 0 = Yes, 1 = No.
-#[derive(Resource, Default)]
-pub struct SelectedConfirmItem(pub usize);
+impl DisplayTarget<'_, '_> {
+    fn apply(&mut self, settings: &GameSettings) {
+        let video_mode = self.monitor_mode(settings.resolution);
+        if let Ok(mut window) = self.windows.single_mut() {
+            window.resolution.set(settings.resolution.0 as f32, settings.resolution.1 as f32);
+            window.mode = window_mode(settings.display_mode, video_mode);
+            window.present_mode = present_mode(settings.vsync);
+        }
+    }
+
+    /// The primary monitor's mode with this size and the highest refresh rate.
+    fn monitor_mode(&self, (width, height): (u32, u32)) -> VideoModeSelection {
+        self.monitors
+            .single()
+            .ok()
+            .and_then(|monitor| {
+                monitor
+                    .video_modes
+                    .iter()
+                    .filter(|m| m.physical_size == UVec2::new(width, height))
+                    .max_by_key(|m| m.refresh_rate_millihertz)
+            })
+            .map_or(VideoModeSelection::Current, |m| VideoModeSelection::Specific(*m))
+    }
+
+    fn monitor_size(&self) -> Option<(u32, u32)> {
+        let monitor = self.monitors.single().ok()?;
+        Some((monitor.physical_width, monitor.physical_height))
+    }
+
+    fn monitor_resolutions(&self) -> Vec<(u32, u32)> {
+        self.monitors
+            .single()
+            .map(|monitor| {
+                monitor
+                    .video_modes
+                    .iter()
+                    .map(|m| (m.physical_size.x, m.physical_size.y))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Tag of each settings row.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    Music,
+    Sound,
+    Resolution,
+    Window,
+    VSync,
+    UiScale,
+    Timer,
+    Ghost,
+    Language,
+    Controls,
+    Accessibility,
+    Save,
+    Reset,
+    Back,
+}
+
+const RESET_DIALOG: &str = "reset_progress";
+const DISCARD_DIALOG: &str = "discard_settings";
+const DISPLAY_DIALOG: &str = "keep_display";
 
 pub struct SettingsPlugin;
 
@@ -55,61 +230,296 @@ impl Plugin for SettingsPlugin {
         app.init_resource::<GameSettings>()
             .init_resource::<SelectedSettingsItem>()
             .init_resource::<SettingsChanged>()
-            .init_resource::<ConfirmingReset>()
-            .init_resource::<SelectedConfirmItem>()
+            .init_resource::<SavedSettings>()
+            .init_resource::<DisplayRevert>()
             .add_systems(OnEnter(GameState::Settings), setup_settings)
+            // Coming back from Controls or Accessibility keeps the unsaved state
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Menu,
+                    entered: GameState::Settings,
+                },
+                snapshot_settings,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::Settings,
+                },
+                snapshot_settings,
+            )
             .add_systems(
                 Update,
-                (
-                    settings_navigation,
-                    settings_mouse,
-                    settings_adjust,
-                    settings_highlight,
-                    settings_update_text,
-                )
+                (settings_action, settings_confirm, display_countdown, settings_highlight_save)
                     .chain()
+                    .in_set(MenuSystems::Respond)
                     .run_if(in_state(GameState::Settings)),
-            );
+            )
+            .add_systems(Update, apply_ui_scale);
     }
 }
 
-fn volume_bar(volume: f32) -> String {
-    let filled = (volume * 10.0).round() as usize;
-    let empty = 10 - filled;
-    let bar: String = "#".repeat(filled) + &"-".repeat(empty);
-    format!("{} {}%", bar, (volume * 100.0).round() as u32)
+/// The window opens before a profile is picked, so its settings are shared by all profiles.
+fn display_path() -> PathBuf {
+    data_dir().join("display.ron")
+}
+
+fn settings_path() -> PathBuf {
+    profile_dir().join("settings.ron")
+}
+
+/// Settings shared by every profile before each had its own, still used by profiles
+/// that haven't saved any.
+fn legacy_settings_path() -> PathBuf {
+    data_dir().join("settings.ron")
+}
+
+/// Reads the window settings, falling back to defaults. Called in `main` before the window
+/// exists; the rest comes with the profile.
+pub fn load_settings() -> GameSettings {
+    let path = display_path();
+    load_settings_from(&if path.is_file() { path } else { legacy_settings_path() })
+}
+
+/// Reads a profile's settings, keeping the window settings from `current`.
+fn profile_settings_from(path: &Path, legacy: &Path, current: &GameSettings) -> GameSettings {
+    let mut settings = load_settings_from(if path.is_file() { path } else { legacy });
+    settings.copy_display_from(current);
+    settings
+}
+
+/// Settings of the profile saving is pointed at, keeping the window settings from `current`.
+pub fn load_profile_settings(current: &GameSettings) -> GameSettings {
+    profile_settings_from(&settings_path(), &legacy_settings_path(), current)
 }
 
-fn row_text(index: usize, settings: &GameSettings) -> String {
-    match index {
-        0 => format!("Music: {}", volume_bar(settings.music_volume)),
-        1 => format!("Sound: {}", volume_bar(settings.sfx_volume)),
-        2 => format!("Resolution: {}x{}", settings.resolution.0, settings.resolution.1),
-        3 => {
-            let mode = if settings.fullscreen {
-                "Fullscreen"
+/// The part of `GameSettings` in `display.ron`. Read back as a `GameSettings`.
+#[derive(Serialize)]
+struct DisplaySettings {
+    resolution: (u32, u32),
+    display_mode: DisplayMode,
+    vsync: bool,
+    ui_scale: Option<f32>,
+}
+
+fn save_display_to(settings: &GameSettings, path: &Path) {
+    let display = DisplaySettings {
+        resolution: settings.resolution,
+        display_mode: settings.display_mode,
+        vsync: settings.vsync,
+        ui_scale: settings.ui_scale,
+    };
+    match ron::ser::to_string_pretty(&display, ron::ser::PrettyConfig::default()) {
+        Ok(text) => {
+            if let Err(e) = storage::write_atomic(path, text.as_bytes()) {
+                error!("Failed to save display settings: {}", e);
+            }
+        }
+        Err(e) => error!("Failed to serialize display settings: {}", e),
+    }
+}
+
+fn load_settings_from(path: &Path) -> GameSettings {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        info!("No settings file, using defaults");
+        return GameSettings::default();
+    };
+    match ron::from_str::<GameSettings>(&contents) {
+        Ok(mut settings) => {
+            if std::mem::take(&mut settings.legacy_fullscreen) {
+                settings.display_mode = DisplayMode::Borderless;
+            }
+            // Hand-edited files can hold anything
+            settings.music_volume = settings.music_volume.clamp(0.0, 1.0);
+            settings.sfx_volume = settings.sfx_volume.clamp(0.0, 1.0);
+            settings
+        }
+        Err(e) => {
+            error!("Failed to parse settings {:?}: {}", path, e);
+            GameSettings::default()
+        }
+    }
+}
+
+fn save_settings_to(settings: &GameSettings, path: &Path) {
+    match ron::ser::to_string_pretty(settings, ron::ser::PrettyConfig::default()) {
+        Ok(text) => {
+            if let Err(e) = storage::write_atomic(path, text.as_bytes()) {
+                error!("Failed to save settings: {}", e);
             } else {
-                "Windowed"
-            };
-            format!("Window: {}", mode)
+                info!("Settings saved");
+            }
         }
-        4 => "Save".to_string(),
-        5 => "Reset Progress".to_string(),
-        6 => "Back".to_string(),
-        _ => String::new(),
+        Err(e) => error!("Failed to serialize settings: {}", e),
     }
 }
 
-fn setup_settings(
-    mut commands: Commands,
-    mut selected: ResMut<SelectedSettingsItem>,
+/// `video_mode` is only used by exclusive fullscreen. `main` passes `Current`,
+/// since monitors aren't known before the window opens.
+pub fn window_mode(mode: DisplayMode, video_mode: VideoModeSelection) -> WindowMode {
+    match mode {
+        DisplayMode::Windowed => WindowMode::Windowed,
+        DisplayMode::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+        DisplayMode::Exclusive => WindowMode::Fullscreen(MonitorSelection::Current, video_mode),
+    }
+}
+
+pub fn present_mode(vsync: bool) -> PresentMode {
+    if vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    }
+}
+
+/// UI is laid out for 1080 logical pixels of height; taller windows scale it up.
+fn auto_ui_scale(window_height: f32) -> f32 {
+    (window_height / 1080.0).max(1.0)
+}
+
+fn apply_ui_scale(
+    settings: Res<GameSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = windows.single() else {
+        return;
+    };
+    let wanted = settings.ui_scale.unwrap_or_else(|| auto_ui_scale(window.height()));
+    if ui_scale.0 != wanted {
+        ui_scale.0 = wanted;
+    }
+}
+
+/// Common resolutions that fit the monitor, plus the monitor's own modes and `current`.
+fn resolution_options(
+    current: (u32, u32),
+    monitor_size: Option<(u32, u32)>,
+    monitor_modes: &[(u32, u32)],
+) -> Vec<(u32, u32)> {
+    let fits = |(w, h): (u32, u32)| monitor_size.is_none_or(|(mw, mh)| w <= mw && h <= mh);
+    let mut resolutions: Vec<(u32, u32)> = RESOLUTIONS.into_iter().filter(|r| fits(*r)).collect();
+    resolutions.extend_from_slice(monitor_modes);
+    // A resolution set by hand in the file stays selectable
+    resolutions.push(current);
+    resolutions.sort();
+    resolutions.dedup();
+    resolutions
+}
+
+fn resolution_text((width, height): (u32, u32)) -> String {
+    format!("{}x{}", width, height)
+}
+
+fn parse_resolution(text: &str) -> Option<(u32, u32)> {
+    let (width, height) = text.split_once('x')?;
+    Some((width.parse().ok()?, height.parse().ok()?))
+}
+
+fn ui_scale_text(scale: Option<f32>, locale: &Locale) -> String {
+    match scale {
+        Some(scale) => format!("{}%", (scale * 100.0).round()),
+        None => locale.get("settings.ui_scale_auto").to_string(),
+    }
+}
+
+/// Anything but a percentage is Auto.
+fn parse_ui_scale(text: &str) -> Option<f32> {
+    let percent: f32 = text.strip_suffix('%')?.parse().ok()?;
+    Some(percent / 100.0)
+}
+
+fn settings_entries(
+    settings: &GameSettings,
+    locale: &Locale,
+    resolutions: Vec<(u32, u32)>,
+) -> Vec<MenuEntry<SettingsRow>> {
+    let resolution_index = resolutions.iter().position(|r| *r == settings.resolution).unwrap_or(0);
+    let display_index = DisplayMode::ALL.iter().position(|m| *m == settings.display_mode).unwrap_or(0);
+    let mut ui_scales = UI_SCALES.to_vec();
+    if !ui_scales.contains(&settings.ui_scale) {
+        ui_scales.push(settings.ui_scale);
+    }
+    let ui_scale_index = ui_scales.iter().position(|s| *s == settings.ui_scale).unwrap_or(0);
+    let language_index = Language::ALL.iter().position(|l| *l == settings.language).unwrap_or(0);
+
+    vec![
+        MenuEntry::slider(SettingsRow::Music, locale.get("settings.music"), settings.music_volume),
+        MenuEntry::slider(SettingsRow::Sound, locale.get("settings.sound"), settings.sfx_volume),
+        MenuEntry::choice(
+            SettingsRow::Resolution,
+            locale.get("settings.resolution"),
+            resolutions.into_iter().map(resolution_text).collect(),
+            resolution_index,
+        ),
+        MenuEntry::choice(
+            SettingsRow::Window,
+            locale.get("settings.window"),
+            DisplayMode::ALL.iter().map(|m| locale.get(m.label_key()).to_string()).collect(),
+            display_index,
+        ),
+        MenuEntry::toggle(
+            SettingsRow::VSync,
+            locale.get("settings.vsync"),
+            settings.vsync,
+            [locale.get("settings.off"), locale.get("settings.on")],
+        ),
+        MenuEntry::choice(
+            SettingsRow::UiScale,
+            locale.get("settings.ui_scale"),
+            ui_scales.into_iter().map(|s| ui_scale_text(s, locale)).collect(),
+            ui_scale_index,
+        ),
+        MenuEntry::toggle(
+            SettingsRow::Timer,
+            locale.get("settings.timer"),
+            settings.show_timer,
+            [locale.get("settings.hidden"), locale.get("settings.shown")],
+        ),
+        MenuEntry::toggle(
+            SettingsRow::Ghost,
+            locale.get("settings.ghost"),
+            settings.show_ghost,
+            [locale.get("settings.off"), locale.get("settings.on")],
+        ),
+        MenuEntry::choice(
+            SettingsRow::Language,
+            locale.get("settings.language"),
+            Language::ALL.iter().map(|l| l.native_name().to_string()).collect(),
+            language_index,
+        ),
+        MenuEntry::button(SettingsRow::Controls, locale.get("settings.controls")),
+        MenuEntry::button(SettingsRow::Accessibility, locale.get("settings.accessibility")),
+        MenuEntry::button(SettingsRow::Save, locale.get("settings.save")),
+        MenuEntry::button(SettingsRow::Reset, locale.get("settings.reset")),
+        MenuEntry::button(SettingsRow::Back, locale.get("settings.back")),
+    ]
+}
+
+fn snapshot_settings(
     settings: Res<GameSettings>,
+    mut saved: ResMut<SavedSettings>,
     mut changed: ResMut<SettingsChanged>,
-    mut confirming: ResMut<ConfirmingReset>,
+    mut selected: ResMut<SelectedSettingsItem>,
 ) {
-    selected.0 = 0;
+    saved.0 = settings.clone();
     changed.0 = false;
-    confirming.0 = false;
+    selected.0 = 0;
+}
+
+fn setup_settings(
+    mut commands: Commands,
+    settings: Res<GameSettings>,
+    selected: Res<SelectedSettingsItem>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+    display: DisplayTarget,
+) {
+    let resolutions = resolution_options(
+        settings.resolution,
+        display.monitor_size(),
+        &display.monitor_resolutions(),
+    );
 
     commands
         .spawn((
@@ -126,11 +536,8 @@ fn setup_settings(
         .with_children(|parent| {
             // Title
             parent.spawn((
-                Text::new("SETTINGS"),
-                TextFont {
-                    font_size: 40.0,
-                    ..default()
-                },
+                Text::new(locale.get("settings.title")),
+                fonts.title(40.0),
                 TextColor(Color::WHITE),
                 Node {
                     margin: UiRect::bottom(Val::Px(30.0)),
@@ -139,274 +546,218 @@ fn setup_settings(
             ));
 
             // Rows
-            for i in 0..SETTINGS_ITEMS {
-                parent
-                    .spawn((
-                        Button,
-                        SettingsRow(i),
-                        Node {
-                            width: Val::Px(400.0),
-                            height: Val::Px(45.0),
-                            justify_content: JustifyContent::Center,
-                            align_items: AlignItems::Center,
-                            margin: UiRect::vertical(Val::Px(2.5)),
-                            ..default()
-                        },
-                        BackgroundColor(COLOR_NORMAL),
-                    ))
-                    .with_children(|btn| {
-                        btn.spawn((
-                            Text::new(row_text(i, &settings)),
-                            TextFont {
-                                font_size: 22.0,
-                                ..default()
-                            },
-                            TextColor(Color::WHITE),
-                        ));
-                    });
-            }
-        });
-}
-
-fn settings_navigation(
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut selected: ResMut<SelectedSettingsItem>,
-    confirming: Res<ConfirmingReset>,
-) {
-    if confirming.0 {
-        return;
-    }
-    if keyboard.just_pressed(KeyCode::ArrowUp) {
-        selected.0 = if selected.0 == 0 {
-            SETTINGS_ITEMS - 1
-        } else {
-            selected.0 - 1
-        };
-    }
-    if keyboard.just_pressed(KeyCode::ArrowDown) {
-        selected.0 = (selected.0 + 1) % SETTINGS_ITEMS;
-    }
-}
-
-fn settings_mouse(
-    mut selected: ResMut<SelectedSettingsItem>,
-    confirming: Res<ConfirmingReset>,
-    mut selected_confirm: ResMut<SelectedConfirmItem>,
-    buttons: Query<(&SettingsRow, &Interaction), Changed<Interaction>>,
-) {
-    for (row, interaction) in &buttons {
-        if *interaction == Interaction::Hovered || *interaction == Interaction::Pressed {
-            if confirming.0 && row.0 >= 100 {
-                selected_confirm.0 = row.0 - 100;
-            } else if !confirming.0 && row.0 < 100 {
-                selected.0 = row.0;
-            }
-        }
-    }
-}
-
-fn spawn_reset_overlay(commands: &mut Commands, selected_confirm: &mut ResMut<SelectedConfirmItem>) {
-    selected_confirm.0 = 1; // Default to "No" for safety
-
-    commands
-        .spawn((
-            ResetOverlay,
-            Node {
-                width: Val::Percent(100.0),
-                height: Val::Percent(100.0),
-                justify_content: JustifyContent::Center,
-                align_items: AlignItems::Center,
-                position_type: PositionType::Absolute,
+            let entries = settings_entries(&settings, &locale, resolutions)
+                .into_iter()
+                .enumerate()
+                .map(|(i, entry)| if i == selected.0 { entry.selected() } else { entry })
+                .collect();
+            let style = MenuStyle {
+                item_width: 400.0,
+                item_height: 38.0,
+                font_size: 20.0,
+                spacing: 4.0,
                 ..default()
-            },
-            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-            GlobalZIndex(200),
-        ))
-        .with_children(|overlay| {
-            overlay
-                .spawn((
-                    Node {
-                        flex_direction: FlexDirection::Column,
-                        justify_content: JustifyContent::Center,
-                        align_items: AlignItems::Center,
-                        padding: UiRect::all(Val::Px(40.0)),
-                        border_radius: BorderRadius::all(Val::Px(12.0)),
-                        ..default()
-                    },
-                    BackgroundColor(Color::srgb(0.12, 0.12, 0.12)),
-                ))
-                .with_children(|panel| {
-                    panel.spawn((
-                        Text::new("Reset Progress?"),
-                        TextFont { font_size: 32.0, ..default() },
-                        TextColor(Color::WHITE),
-                        Node { margin: UiRect::bottom(Val::Px(10.0)), ..default() },
-                    ));
-                    panel.spawn((
-                        Text::new("All level progress will be lost."),
-                        TextFont { font_size: 18.0, ..default() },
-                        TextColor(Color::srgb(0.8, 0.4, 0.4)),
-                        Node { margin: UiRect::bottom(Val::Px(30.0)), ..default() },
-                    ));
-                    panel
-                        .spawn(Node {
-                            flex_direction: FlexDirection::Row,
-                            justify_content: JustifyContent::Center,
-                            column_gap: Val::Px(20.0),
-                            ..default()
-                        })
-                        .with_children(|row| {
-                            for (i, label) in ["Yes", "No"].iter().enumerate() {
-                                row.spawn((
-                                    Button,
-                                    SettingsRow(100 + i),
-                                    Node {
-                                        width: Val::Px(120.0),
-                                        height: Val::Px(45.0),
-                                        justify_content: JustifyContent::Center,
-                                        align_items: AlignItems::Center,
-                                        ..default()
-                                    },
-                                    BackgroundColor(COLOR_NORMAL),
-                                ))
-                                .with_children(|btn| {
-                                    btn.spawn((
-                                        Text::new(*label),
-                                        TextFont { font_size: 22.0, ..default() },
-                                        TextColor(Color::WHITE),
-                                    ));
-                                });
-                            }
-                        });
-                });
+            };
+            spawn_menu(parent, &fonts, style, entries);
         });
 }
 
 #[allow(clippy::too_many_arguments)]
-fn settings_adjust(
-    mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    selected: Res<SelectedSettingsItem>,
+fn settings_action(
+    mut events: MessageReader<MenuEvent>,
+    mut dialogs: MessageWriter<OpenDialog>,
+    rows: Query<(&SettingsRow, &MenuItem, &Widget)>,
     mut settings: ResMut<GameSettings>,
     mut next_state: ResMut<NextState<GameState>>,
     origin: Res<SettingsOrigin>,
-    mut windows: Query<&mut Window>,
+    mut display: DisplayTarget,
+    mut revert: ResMut<DisplayRevert>,
     mut changed: ResMut<SettingsChanged>,
-    mut confirming: ResMut<ConfirmingReset>,
-    mut progress: ResMut<PlayerProgress>,
-    mut selected_confirm: ResMut<SelectedConfirmItem>,
-    overlay_query: Query<Entity, With<ResetOverlay>>,
-    buttons_interaction: Query<(&SettingsRow, &Interaction)>,
+    mut saved: ResMut<SavedSettings>,
+    mut selected: ResMut<SelectedSettingsItem>,
+    locale: Res<Locale>,
 ) {
-    if confirming.0 {
-        if keyboard.just_pressed(KeyCode::Escape) {
-            for entity in &overlay_query {
-                commands.entity(entity).despawn();
+    for event in events.read() {
+        let item = match *event {
+            MenuEvent::Activated { item, .. } | MenuEvent::Changed { item, .. } => item,
+            MenuEvent::Back { .. } => {
+                leave_settings(&mut dialogs, changed.0, &origin, &mut next_state, &locale);
+                continue;
             }
-            confirming.0 = false;
-            return;
-        }
-        if keyboard.just_pressed(KeyCode::ArrowLeft) {
-            selected_confirm.0 = 0;
-        }
-        if keyboard.just_pressed(KeyCode::ArrowRight) {
-            selected_confirm.0 = 1;
-        }
-        let enter = keyboard.just_pressed(KeyCode::Enter);
-        let popup_clicked = buttons_interaction
-            .iter()
-            .any(|(row, i)| row.0 >= 100 && *i == Interaction::Pressed);
-        if enter || popup_clicked {
-            if selected_confirm.0 == 0 {
-                progress.max_unlocked_level = 0;
-                save_progress(&progress);
+        };
+        let Ok((row, menu_item, widget)) = rows.get(item) else {
+            continue;
+        };
+
+        let before = settings.clone();
+        match (row, widget) {
+            (SettingsRow::Music, Widget::Slider { value, .. }) => settings.music_volume = *value,
+            (SettingsRow::Sound, Widget::Slider { value, .. }) => settings.sfx_volume = *value,
+            (SettingsRow::Resolution, Widget::Choice { options, index }) => {
+                let Some(resolution) = options.get(*index).and_then(|o| parse_resolution(o)) else {
+                    continue;
+                };
+                settings.resolution = resolution;
             }
-            for entity in &overlay_query {
-                commands.entity(entity).despawn();
+            (SettingsRow::Window, Widget::Choice { index, .. }) => settings.display_mode = DisplayMode::ALL[*index],
+            (SettingsRow::VSync, Widget::Toggle { on, .. }) => settings.vsync = *on,
+            (SettingsRow::UiScale, Widget::Choice { options, index }) => {
+                settings.ui_scale = options.get(*index).and_then(|o| parse_ui_scale(o));
             }
-            confirming.0 = false;
-        }
-        return;
-    }
-
-    if keyboard.just_pressed(KeyCode::Escape) {
-        go_back(&origin, &mut next_state);
-        return;
-    }
-
-    let enter = keyboard.just_pressed(KeyCode::Enter);
-    let row_clicked = buttons_interaction
-        .iter()
-        .find(|(row, i)| row.0 < 100 && **i == Interaction::Pressed)
-        .map(|(row, _)| row.0);
-
-    if enter || row_clicked.is_some() {
-        let action_row = row_clicked.unwrap_or(selected.0);
-        match action_row {
-            4 => {
+            (SettingsRow::Timer, Widget::Toggle { on, .. }) => settings.show_timer = *on,
+            (SettingsRow::Ghost, Widget::Toggle { on, .. }) => settings.show_ghost = *on,
+            (SettingsRow::Language, Widget::Choice { index, .. }) => {
+                settings.language = Language::ALL[*index];
+                // Re-entering the state rebuilds the rows in the new language
+                selected.0 = menu_item.index;
+                next_state.set(GameState::Settings);
+            }
+            (SettingsRow::Controls, _) => {
+                selected.0 = menu_item.index;
+                next_state.set(GameState::Controls);
+                continue;
+            }
+            (SettingsRow::Accessibility, _) => {
+                selected.0 = menu_item.index;
+                next_state.set(GameState::Accessibility);
+                continue;
+            }
+            (SettingsRow::Save, _) => {
+                save_display_to(&settings, &display_path());
+                save_settings_to(&settings, &settings_path());
+                saved.0 = settings.clone();
                 changed.0 = false;
-                return;
+                continue;
             }
-            5 => {
-                confirming.0 = true;
-                spawn_reset_overlay(&mut commands, &mut selected_confirm);
-                return;
+            (SettingsRow::Reset, _) => {
+                dialogs.write(OpenDialog::confirm(
+                    RESET_DIALOG,
+                    locale.get("settings.reset_title"),
+                    locale.get("settings.reset_message"),
+                    &locale,
+                ));
+                continue;
             }
-            6 => {
-                go_back(&origin, &mut next_state);
-                return;
+            (SettingsRow::Back, _) => {
+                leave_settings(&mut dialogs, changed.0, &origin, &mut next_state, &locale);
+                continue;
+            }
+            _ => continue,
+        }
+
+        let display_row = matches!(
+            row,
+            SettingsRow::Resolution | SettingsRow::Window | SettingsRow::VSync | SettingsRow::UiScale
+        );
+        if display_row {
+            display.apply(&settings);
+            if revert.previous.is_none() {
+                revert.previous = Some(before);
             }
-            _ => {}
+            revert.remaining = REVERT_SECONDS;
+            revert.row = menu_item.index;
+            dialogs.write(keep_display_dialog(&locale, REVERT_SECONDS));
         }
+        changed.0 = true;
     }
+}
 
-    let left = keyboard.just_pressed(KeyCode::ArrowLeft);
-    let right = keyboard.just_pressed(KeyCode::ArrowRight);
+fn countdown_text(locale: &Locale, remaining: f32) -> String {
+    locale.format("settings.display_message", &[("seconds", &(remaining.ceil() as u32))])
+}
 
-    if !left && !right {
-        return;
+fn keep_display_dialog(locale: &Locale, remaining: f32) -> OpenDialog {
+    OpenDialog {
+        id: DISPLAY_DIALOG,
+        title: locale.get("settings.display_title").to_string(),
+        message: countdown_text(locale, remaining),
+        buttons: vec![locale.get("settings.keep").to_string(), locale.get("settings.revert").to_string()],
+        // If the new mode can't be read, Enter and waiting both go back
+        default: 1,
+        danger: None,
     }
+}
 
-    match selected.0 {
-        0 => {
-            if right {
-                settings.music_volume = (settings.music_volume + 0.1).min(1.0);
-            } else {
-                settings.music_volume = (settings.music_volume - 0.1).max(0.0);
-            }
-            changed.0 = true;
-        }
-        1 => {
-            if right {
-                settings.sfx_volume = (settings.sfx_volume + 0.1).min(1.0);
-            } else {
-                settings.sfx_volume = (settings.sfx_volume - 0.1).max(0.0);
-            }
-            changed.0 = true;
+/// Counts down the keep-display dialog and closes it unanswered when time is up.
+fn display_countdown(
+    time: Res<Time<Real>>,
+    mut revert: ResMut<DisplayRevert>,
+    locale: Res<Locale>,
+    mut messages: Query<&mut Text, With<ModalMessage>>,
+    mut close: MessageWriter<CloseDialog>,
+) {
+    if revert.previous.is_none() {
+        return;
+    }
+    revert.remaining -= time.delta_secs();
+    if revert.remaining <= 0.0 {
+        close.write(CloseDialog(DISPLAY_DIALOG));
+        return;
+    }
+    let text = countdown_text(&locale, revert.remaining);
+    for mut message in &mut messages {
+        if **message != text {
+            **message = text.clone();
         }
-        2 => {
-            settings.resolution = if settings.resolution == (1280, 720) {
-                (1920, 1080)
-            } else {
-                (1280, 720)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn settings_confirm(
+    mut answers: MessageReader<DialogClosed>,
+    mut settings: ResMut<GameSettings>,
+    saved: Res<SavedSettings>,
+    mut progress: ResMut<PlayerProgress>,
+    origin: Res<SettingsOrigin>,
+    mut display: DisplayTarget,
+    mut revert: ResMut<DisplayRevert>,
+    mut changed: ResMut<SettingsChanged>,
+    mut selected: ResMut<SelectedSettingsItem>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for answer in answers.read() {
+        if answer.id == DISPLAY_DIALOG {
+            let Some(previous) = revert.previous.take() else {
+                continue;
             };
-            if let Ok(mut window) = windows.single_mut() {
-                window.resolution.set(settings.resolution.0 as f32, settings.resolution.1 as f32);
+            if !answer.confirmed(DISPLAY_DIALOG) {
+                settings.copy_display_from(&previous);
+                display.apply(&settings);
+                changed.0 = *settings != saved.0;
+                // Rebuild so the rows show the restored values
+                selected.0 = revert.row;
+                next_state.set(GameState::Settings);
             }
-            changed.0 = true;
         }
-        3 => {
-            settings.fullscreen = !settings.fullscreen;
-            if let Ok(mut window) = windows.single_mut() {
-                window.mode = if settings.fullscreen {
-                    WindowMode::BorderlessFullscreen(MonitorSelection::Current)
-                } else {
-                    WindowMode::Windowed
-                };
-            }
-            changed.0 = true;
+        if answer.confirmed(RESET_DIALOG) {
+            *progress = PlayerProgress::default();
+            save_progress(&progress);
+        }
+        if answer.confirmed(DISCARD_DIALOG) {
+            *settings = saved.0.clone();
+            display.apply(&settings);
+            go_back(&origin, &mut next_state);
         }
-        _ => {}
+    }
+}
+
+/// Asks before throwing away unsaved changes; otherwise leaves right away.
+fn leave_settings(
+    dialogs: &mut MessageWriter<OpenDialog>,
+    has_changes: bool,
+    origin: &SettingsOrigin,
+    next_state: &mut ResMut<NextState<GameState>>,
+    locale: &Locale,
+) {
+    if has_changes {
+        dialogs.write(OpenDialog::confirm(
+            DISCARD_DIALOG,
+            locale.get("settings.discard_title"),
+            locale.get("settings.discard_message"),
+            locale,
+        ));
+    } else {
+        go_back(origin, next_state);
     }
 }
 
@@ -417,57 +768,159 @@ fn go_back(origin: &SettingsOrigin, next_state: &mut ResMut<NextState<GameState>
     }
 }
 
-fn settings_highlight(
-    selected: Res<SelectedSettingsItem>,
-    changed: Res<SettingsChanged>,
-    confirming: Res<ConfirmingReset>,
-    selected_confirm: Res<SelectedConfirmItem>,
-    mut rows: Query<(&SettingsRow, &mut BackgroundColor)>,
-) {
-    for (row, mut bg) in &mut rows {
-        let is_selected = row.0 == selected.0;
-
-        if row.0 >= 100 {
-            let confirm_index = row.0 - 100;
-            if confirming.0 && confirm_index == selected_confirm.0 {
-                if confirm_index == 0 {
-                    *bg = BackgroundColor(Color::srgb(0.7, 0.15, 0.15));
-                } else {
-                    *bg = BackgroundColor(COLOR_SELECTED);
-                }
-            } else {
-                *bg = BackgroundColor(COLOR_NORMAL);
-            }
+/// Save turns green while there are unsaved changes.
+fn settings_highlight_save(changed: Res<SettingsChanged>, mut rows: Query<(&SettingsRow, &mut ItemColors)>) {
+    for (row, mut colors) in &mut rows {
+        if *row != SettingsRow::Save {
             continue;
         }
-
-        if row.0 == 4 && changed.0 {
-            if is_selected {
-                *bg = BackgroundColor(Color::srgb(0.2, 0.8, 0.2));
-            } else {
-                *bg = BackgroundColor(Color::srgb(0.1, 0.4, 0.1));
+        let wanted = if changed.0 {
+            ItemColors {
+                normal: COLOR_UNSAVED,
+                selected: COLOR_UNSAVED_SELECTED,
             }
-        } else if is_selected {
-            *bg = BackgroundColor(COLOR_SELECTED);
         } else {
-            *bg = BackgroundColor(COLOR_NORMAL);
+            ItemColors::default()
+        };
+        if *colors != wanted {
+            *colors = wanted;
         }
     }
 }
 
-fn settings_update_text(
-    settings: Res<GameSettings>,
-    rows: Query<(&SettingsRow, &Children)>,
-    mut texts: Query<&mut Text>,
-) {
-    for (row, children) in &rows {
-        if row.0 >= 100 {
-            continue;
-        }
-        for child in children.iter() {
-            if let Ok(mut text) = texts.get_mut(child) {
-                **text = row_text(row.0, &settings);
-            }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_file_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.ron");
+
+        let settings = GameSettings {
+            music_volume: 0.3,
+            resolution: (1920, 1080),
+            display_mode: DisplayMode::Exclusive,
+            vsync: false,
+            ui_scale: Some(1.5),
+            show_ghost: false,
+            language: Language::Russian,
+            palette: Palette::RedGreen,
+            hazard_shapes: true,
+            reduced_motion: true,
+            game_speed: 0.7,
+            invincible: true,
+            air_jumps: 2,
+            ..default()
+        };
+        save_settings_to(&settings, &path);
+        assert_eq!(load_settings_from(&path), settings);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.ron");
+        std::fs::write(&path, "(music_volume: 0.2)").unwrap();
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.music_volume, 0.2);
+        assert_eq!(settings.resolution, GameSettings::default().resolution);
+    }
+
+    #[test]
+    fn volumes_clamped_on_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.ron");
+        std::fs::write(&path, "(music_volume: 2.5, sfx_volume: -1.0)").unwrap();
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.music_volume, 1.0);
+        assert_eq!(settings.sfx_volume, 0.0);
+    }
+
+    #[test]
+    fn old_fullscreen_flag_becomes_borderless() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.ron");
+        std::fs::write(&path, "(fullscreen: true, resolution: (1920, 1080))").unwrap();
+
+        let settings = load_settings_from(&path);
+        assert_eq!(settings.display_mode, DisplayMode::Borderless);
+        assert!(!settings.legacy_fullscreen);
+
+        // Saved again, the old field is gone
+        save_settings_to(&settings, &path);
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("fullscreen"));
+    }
+
+    #[test]
+    fn display_settings_are_shared_and_the_rest_per_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let display = dir.path().join("display.ron");
+        let profile = dir.path().join("settings.ron");
+        let legacy = dir.path().join("legacy.ron");
+
+        let saved = GameSettings {
+            resolution: (1920, 1080),
+            vsync: false,
+            language: Language::Russian,
+            ..default()
+        };
+        save_display_to(&saved, &display);
+        save_settings_to(&saved, &profile);
+
+        // Only the window settings are in the shared file
+        let shared = load_settings_from(&display);
+        assert_eq!(shared.resolution, (1920, 1080));
+        assert!(!shared.vsync);
+        assert_eq!(shared.language, Language::English);
+
+        // Switching profiles keeps the window as it is
+        let current = GameSettings {
+            resolution: (1280, 720),
+            ..default()
+        };
+        let loaded = profile_settings_from(&profile, &legacy, &current);
+        assert_eq!(loaded.language, Language::Russian);
+        assert_eq!(loaded.resolution, (1280, 720));
+        assert!(loaded.vsync);
+
+        // A profile without its own settings starts from the old shared file
+        std::fs::write(&legacy, "(music_volume: 0.2)").unwrap();
+        let fresh = profile_settings_from(&dir.path().join("missing.ron"), &legacy, &current);
+        assert_eq!(fresh.music_volume, 0.2);
+    }
+
+    #[test]
+    fn resolutions_fit_the_monitor() {
+        let options = resolution_options((1000, 700), Some((1920, 1080)), &[(1920, 1080), (1680, 1050)]);
+        assert_eq!(
+            options,
+            vec![(1000, 700), (1280, 720), (1366, 768), (1600, 900), (1680, 1050), (1920, 1080)]
+        );
+
+        // Without a known monitor every common resolution is offered
+        assert_eq!(resolution_options((1280, 720), None, &[]), RESOLUTIONS.to_vec());
+    }
+
+    #[test]
+    fn ui_scale_text_roundtrip() {
+        let locale = Locale::default();
+        for scale in UI_SCALES {
+            assert_eq!(parse_ui_scale(&ui_scale_text(scale, &locale)), scale);
         }
+        assert_eq!(auto_ui_scale(720.0), 1.0);
+        assert_eq!(auto_ui_scale(2160.0), 2.0);
+    }
+
+    #[test]
+    fn broken_or_missing_file_gives_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("settings.ron");
+        assert_eq!(load_settings_from(&path), GameSettings::default());
+
+        std::fs::write(&path, "not ron at all").unwrap();
+        assert_eq!(load_settings_from(&path), GameSettings::default());
     }
 }
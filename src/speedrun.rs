@@ -0,0 +1,263 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::fonts::UiFonts;
+use crate::level::{check_exit, CurrentLevel, LevelCompleted, LEVELS};
+use crate::locale::Locale;
+use crate::progress::PlayerProgress;
+use crate::settings::GameSettings;
+use crate::states::GameState;
+
+/// Level and run timing. Ticks with physics time, so pauses are excluded.
+#[derive(Resource, Default)]
+pub struct SpeedrunTimer {
+    pub level_time: f32,
+    /// Completed level times of the current run, in order.
+    pub splits: Vec<f32>,
+    /// The run started at level 1, so it counts towards the full-run record.
+    pub full_run: bool,
+    /// Levels that got a new personal best during this run.
+    pub level_records: Vec<usize>,
+    /// The finished run beat the full-run personal best.
+    pub run_record: bool,
+    /// A level of this run was completed or skipped with an assist, so the run sets no record.
+    pub assisted: bool,
+}
+
+impl SpeedrunTimer {
+    pub fn run_time(&self) -> f32 {
+        self.splits.iter().sum::<f32>() + self.level_time
+    }
+}
+
+#[derive(Component)]
+struct TimerHud;
+
+pub struct SpeedrunPlugin;
+
+impl Plugin for SpeedrunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpeedrunTimer>()
+            .add_systems(
+                OnTransition {
+                    exited: GameState::LevelSelect,
+                    entered: GameState::Playing,
+                },
+                start_run,
+            )
+            // Every entry to Playing restarts the level, resuming from pause included
+            .add_systems(OnEnter(GameState::Playing), (reset_level_time, spawn_timer_hud))
+            .add_systems(
+                FixedUpdate,
+                tick_timer.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(Update, record_split.after(check_exit))
+            .add_systems(
+                Update,
+                update_timer_hud.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Formats seconds as `m:ss.cc`.
+pub fn format_time(secs: f32) -> String {
+    let centis = (secs.max(0.0) * 100.0).round() as u64;
+    format!("{}:{:02}.{:02}", centis / 6000, (centis / 100) % 60, centis % 100)
+}
+
+fn start_run(mut timer: ResMut<SpeedrunTimer>, current_level: Res<CurrentLevel>) {
+    *timer = SpeedrunTimer {
+        full_run: current_level.0 == 0,
+        ..default()
+    };
+}
+
+fn reset_level_time(mut timer: ResMut<SpeedrunTimer>) {
+    timer.level_time = 0.0;
+}
+
+fn tick_timer(physics_time: Res<Time<Physics>>, mut timer: ResMut<SpeedrunTimer>) {
+    timer.level_time += physics_time.delta_secs();
+}
+
+/// Records are saved with the level's statistics once the level ends.
+pub fn record_split(
+    mut completed: MessageReader<LevelCompleted>,
+    mut timer: ResMut<SpeedrunTimer>,
+    mut progress: ResMut<PlayerProgress>,
+) {
+    for event in completed.read() {
+        let time = timer.level_time;
+        timer.level_time = 0.0;
+        timer.splits.push(time);
+        timer.assisted |= event.assisted;
+        info!("Level {} split: {}", event.index + 1, format_time(time));
+
+        if !event.assisted && progress.record_level_time(event.index, time) {
+            timer.level_records.push(event.index);
+        }
+
+        if timer.full_run && !timer.assisted && event.index + 1 == LEVELS.len() {
+            let run_time = timer.run_time();
+            let splits = timer.splits.clone();
+            if progress.record_run(run_time, &splits) {
+                timer.run_record = true;
+                info!("New full-run record: {}", format_time(run_time));
+            }
+        }
+    }
+}
+
+fn spawn_timer_hud(mut commands: Commands, settings: Res<GameSettings>, fonts: Res<UiFonts>) {
+    let visibility = if settings.show_timer {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    commands.spawn((
+        TimerHud,
+        Text::new(""),
+        fonts.text(24.0),
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(Justify::Right),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(20.0),
+            top: Val::Px(20.0),
+            ..default()
+        },
+        visibility,
+        DespawnOnExit::<GameState>(GameState::Playing),
+    ));
+}
+
+fn update_timer_hud(
+    timer: Res<SpeedrunTimer>,
+    progress: Res<PlayerProgress>,
+    current_level: Res<CurrentLevel>,
+    locale: Res<Locale>,
+    mut query: Query<&mut Text, With<TimerHud>>,
+) {
+    for mut text in &mut query {
+        let best = progress
+            .best_level_time(current_level.0)
+            .map(|t| locale.format("hud.best", &[("time", &format_time(t))]))
+            .unwrap_or_default();
+        **text = locale.format(
+            "hud.timer",
+            &[
+                ("level", &format_time(timer.level_time)),
+                ("best", &best),
+                ("run", &format_time(timer.run_time())),
+            ],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_time_pads_fields() {
+        assert_eq!(format_time(0.0), "0:00.00");
+        assert_eq!(format_time(5.25), "0:05.25");
+        assert_eq!(format_time(83.456), "1:23.46");
+        assert_eq!(format_time(600.0), "10:00.00");
+    }
+
+    #[test]
+    fn split_recorded_on_level_complete() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<LevelCompleted>();
+        // Existing record is faster, so it stays
+        app.insert_resource(PlayerProgress {
+            best_level_times: vec![None, Some(11.0)],
+            ..default()
+        });
+        app.insert_resource(SpeedrunTimer {
+            level_time: 12.5,
+            splits: vec![10.0],
+            full_run: true,
+            ..default()
+        });
+        app.add_systems(Update, record_split);
+
+        app.world_mut().write_message(LevelCompleted { index: 1, assisted: false });
+        app.update();
+
+        let timer = app.world().resource::<SpeedrunTimer>();
+        assert_eq!(timer.splits, vec![10.0, 12.5]);
+        assert_eq!(timer.level_time, 0.0);
+        assert_eq!(timer.run_time(), 22.5);
+        assert!(timer.level_records.is_empty());
+
+        let progress = app.world().resource::<PlayerProgress>();
+        assert_eq!(progress.best_level_time(1), Some(11.0));
+    }
+
+    #[test]
+    fn resuming_restarts_the_level_time() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin, SpeedrunPlugin));
+        app.init_state::<GameState>();
+        app.add_message::<LevelCompleted>();
+        app.init_resource::<Time<Physics>>();
+        app.init_resource::<PlayerProgress>();
+        app.init_resource::<GameSettings>();
+        app.init_resource::<Locale>();
+        app.init_resource::<UiFonts>();
+        app.insert_resource(CurrentLevel(0));
+        app.insert_resource(SpeedrunTimer {
+            splits: vec![10.0],
+            ..default()
+        });
+        let enter = |app: &mut App, state| {
+            app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+        };
+
+        enter(&mut app, GameState::Playing);
+        app.world_mut().resource_mut::<SpeedrunTimer>().level_time = 7.5;
+        enter(&mut app, GameState::Paused);
+        assert_eq!(app.world().resource::<SpeedrunTimer>().level_time, 7.5);
+
+        // The attempt before the pause is thrown away; earlier splits stay
+        enter(&mut app, GameState::Playing);
+        let timer = app.world().resource::<SpeedrunTimer>();
+        assert_eq!(timer.level_time, 0.0);
+        assert_eq!(timer.splits, vec![10.0]);
+    }
+
+    #[test]
+    fn assisted_level_sets_no_record() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<LevelCompleted>();
+        app.init_resource::<PlayerProgress>();
+        app.insert_resource(SpeedrunTimer {
+            level_time: 5.0,
+            splits: vec![10.0; LEVELS.len() - 1],
+            full_run: true,
+            ..default()
+        });
+        app.add_systems(Update, record_split);
+
+        app.world_mut().write_message(LevelCompleted {
+            index: LEVELS.len() - 1,
+            assisted: true,
+        });
+        app.update();
+
+        let timer = app.world().resource::<SpeedrunTimer>();
+        assert!(timer.assisted);
+        assert!(!timer.run_record);
+        assert!(timer.level_records.is_empty());
+
+        let progress = app.world().resource::<PlayerProgress>();
+        assert_eq!(progress.best_level_time(LEVELS.len() - 1), None);
+        assert!(progress.best_run.is_none());
+    }
+}
@@ -3,12 +3,16 @@ use bevy::prelude::*;
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
 pub enum GameState {
     #[default]
+    ProfileSelect,
     Menu,
     LevelSelect,
     LevelTransition,
     Playing,
     Paused,
     Settings,
+    Controls,
+    Accessibility,
+    Statistics,
     Victory,
 }
 
@@ -0,0 +1,284 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use crate::fonts::UiFonts;
+use crate::level::{check_exit, playing_bundled_level, CurrentLevel, LevelCompleted, LEVELS};
+use crate::locale::Locale;
+use crate::player::PlayerDied;
+use crate::progress::{LevelStats, PlayerProgress, save_progress};
+use crate::speedrun::{format_time, record_split};
+use crate::states::GameState;
+use crate::widgets::{spawn_menu, MenuEntry, MenuEvent, MenuStyle, MenuSystems};
+
+const COLOR_HEADER: Color = Color::srgb(0.9, 0.8, 0.2);
+
+#[derive(Component)]
+struct StatisticsBack;
+
+pub struct StatisticsPlugin;
+
+impl Plugin for StatisticsPlugin {
+    fn build(&self, app: &mut App) {
+        // Saved when a level ends or is quit, not on every pause, so backups stay distinct
+        app.add_systems(OnEnter(GameState::Playing), count_attempt.run_if(playing_bundled_level))
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Paused,
+                    entered: GameState::Menu,
+                },
+                save_stats,
+            )
+            .add_systems(
+                FixedUpdate,
+                track_play_time
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(playing_bundled_level),
+            )
+            .add_systems(
+                Update,
+                (
+                    count_deaths.run_if(playing_bundled_level),
+                    (count_completions, save_stats.run_if(on_message::<LevelCompleted>))
+                        .chain()
+                        .after(check_exit)
+                        .after(record_split),
+                ),
+            )
+            // Closing the window writes `AppExit` in PostUpdate, and the app stops after this frame
+            .add_systems(Last, save_stats_on_exit)
+            .add_systems(OnEnter(GameState::Statistics), setup_statistics)
+            .add_systems(
+                Update,
+                statistics_action
+                    .in_set(MenuSystems::Respond)
+                    .run_if(in_state(GameState::Statistics)),
+            );
+    }
+}
+
+fn count_attempt(current_level: Res<CurrentLevel>, mut progress: ResMut<PlayerProgress>) {
+    progress.level_stats_mut(current_level.0).attempts += 1;
+}
+
+fn count_deaths(
+    mut deaths: MessageReader<PlayerDied>,
+    current_level: Res<CurrentLevel>,
+    mut progress: ResMut<PlayerProgress>,
+) {
+    let count = deaths.read().count() as u32;
+    if count > 0 {
+        progress.level_stats_mut(current_level.0).deaths += count;
+    }
+}
+
+fn count_completions(mut completed: MessageReader<LevelCompleted>, mut progress: ResMut<PlayerProgress>) {
+    for event in completed.read() {
+        let stats = progress.level_stats_mut(event.index);
+        if event.assisted {
+            stats.assisted_completions += 1;
+        } else {
+            stats.completions += 1;
+        }
+    }
+}
+
+fn track_play_time(
+    physics_time: Res<Time<Physics>>,
+    current_level: Res<CurrentLevel>,
+    mut progress: ResMut<PlayerProgress>,
+) {
+    progress.level_stats_mut(current_level.0).play_time += physics_time.delta_secs();
+}
+
+fn save_stats(progress: Res<PlayerProgress>) {
+    save_progress(&progress);
+}
+
+fn save_stats_on_exit(
+    mut exit_events: MessageReader<AppExit>,
+    state: Res<State<GameState>>,
+    progress: Res<PlayerProgress>,
+) {
+    // Closing the window mid-level, paused or not
+    let in_level = matches!(state.get(), GameState::Playing | GameState::Paused);
+    if exit_events.read().count() > 0 && in_level {
+        save_progress(&progress);
+    }
+}
+
+fn stats_line(locale: &Locale, label: &str, stats: &LevelStats, best: Option<f32>) -> String {
+    let best = best.map(format_time).unwrap_or_else(|| "-".to_string());
+    locale.format(
+        "statistics.line",
+        &[
+            ("label", &label),
+            ("attempts", &stats.attempts),
+            ("cleared", &stats.completions),
+            ("assisted", &stats.assisted_completions),
+            ("deaths", &stats.deaths),
+            ("best", &best),
+            ("time", &format_time(stats.play_time)),
+        ],
+    )
+}
+
+fn total_stats(progress: &PlayerProgress) -> LevelStats {
+    progress
+        .level_stats
+        .iter()
+        .fold(LevelStats::default(), |acc, s| LevelStats {
+            attempts: acc.attempts + s.attempts,
+            completions: acc.completions + s.completions,
+            assisted_completions: acc.assisted_completions + s.assisted_completions,
+            deaths: acc.deaths + s.deaths,
+            play_time: acc.play_time + s.play_time,
+        })
+}
+
+fn setup_statistics(
+    mut commands: Commands,
+    progress: Res<PlayerProgress>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DespawnOnExit::<GameState>(GameState::Statistics),
+        ))
+        .with_children(|parent| {
+            // Title
+            parent.spawn((
+                Text::new(locale.get("statistics.title")),
+                fonts.title(40.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(30.0)),
+                    ..default()
+                },
+            ));
+
+            // One line per level
+            for i in 0..LEVELS.len() {
+                let label = locale.format("statistics.level", &[("n", &(i + 1))]);
+                parent.spawn((
+                    Text::new(stats_line(&locale, &label, &progress.level_stats(i), progress.best_level_time(i))),
+                    fonts.text(20.0),
+                    TextColor(Color::WHITE),
+                    Node {
+                        margin: UiRect::vertical(Val::Px(4.0)),
+                        ..default()
+                    },
+                ));
+            }
+
+            // Totals
+            let best_run = progress.best_run.as_ref().map(|run| run.time);
+            parent.spawn((
+                Text::new(stats_line(&locale, locale.get("statistics.total"), &total_stats(&progress), best_run)),
+                fonts.text(20.0),
+                TextColor(COLOR_HEADER),
+                Node {
+                    margin: UiRect::vertical(Val::Px(12.0)),
+                    ..default()
+                },
+            ));
+
+            // Back button
+            let back = MenuEntry::button(StatisticsBack, locale.get("statistics.back")).gap_before(20.0);
+            spawn_menu(parent, &fonts, MenuStyle::default(), vec![back]);
+        });
+}
+
+fn statistics_action(
+    mut events: MessageReader<MenuEvent>,
+    buttons: Query<(), With<StatisticsBack>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in events.read() {
+        let back = match event {
+            MenuEvent::Activated { item, .. } => buttons.contains(*item),
+            MenuEvent::Back { .. } => true,
+            MenuEvent::Changed { .. } => false,
+        };
+        if back {
+            next_state.set(GameState::Menu);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::level::CustomLevel;
+
+    #[test]
+    fn deaths_and_completions_counted_per_level() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.add_message::<PlayerDied>();
+        app.add_message::<LevelCompleted>();
+        app.insert_resource(CurrentLevel(1));
+        app.init_resource::<PlayerProgress>();
+        app.add_systems(Update, (count_deaths, count_completions));
+
+        app.world_mut().write_message(PlayerDied::default());
+        app.world_mut().write_message(PlayerDied::default());
+        app.world_mut().write_message(LevelCompleted { index: 1, assisted: false });
+        app.world_mut().write_message(LevelCompleted { index: 1, assisted: true });
+        app.update();
+
+        let progress = app.world().resource::<PlayerProgress>();
+        let stats = progress.level_stats(1);
+        assert_eq!(stats.deaths, 2);
+        assert_eq!(stats.completions, 1);
+        assert_eq!(stats.assisted_completions, 1);
+        assert_eq!(progress.level_stats(0), LevelStats::default());
+    }
+
+    #[test]
+    fn every_entry_to_playing_is_an_attempt() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin));
+        app.init_state::<GameState>();
+        app.insert_resource(CurrentLevel(2));
+        app.init_resource::<CustomLevel>();
+        app.init_resource::<PlayerProgress>();
+        app.add_systems(OnEnter(GameState::Playing), count_attempt.run_if(playing_bundled_level));
+
+        // Resuming restarts the level from the spawn point, so it's another attempt too
+        for state in [
+            GameState::LevelSelect,
+            GameState::Playing,
+            GameState::Paused,
+            GameState::Playing,
+            GameState::LevelTransition,
+            GameState::Playing,
+        ] {
+            app.world_mut().resource_mut::<NextState<GameState>>().set(state);
+            app.update();
+        }
+        assert_eq!(app.world().resource::<PlayerProgress>().level_stats(2).attempts, 3);
+    }
+
+    #[test]
+    fn totals_sum_all_levels() {
+        let mut progress = PlayerProgress::default();
+        progress.level_stats_mut(0).deaths = 3;
+        progress.level_stats_mut(0).play_time = 10.0;
+        progress.level_stats_mut(2).deaths = 4;
+        progress.level_stats_mut(2).attempts = 2;
+
+        let total = total_stats(&progress);
+        assert_eq!(total.deaths, 7);
+        assert_eq!(total.attempts, 2);
+        assert_eq!(total.play_time, 10.0);
+    }
+}
@@ -0,0 +1,107 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// `save.json` + `.bak` -> `save.json.bak`.
+pub fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name: OsString = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Writes `contents` so that `path` always holds either the old or the new data:
+/// write a temp file next to it, fsync, then rename it over the original.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = with_suffix(path, ".tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        std::fs::remove_file(&tmp).ok();
+        return Err(e);
+    }
+
+    sync_parent_dir(path)
+}
+
+/// Persists the rename itself. Directories can't be opened for syncing on Windows.
+#[cfg(unix)]
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        Some(dir) => File::open(dir)?.sync_all(),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_parent_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Rotating backups of `path`, newest first: `path.1`, `path.2`, ...
+pub fn backup_paths(path: &Path, keep: usize) -> Vec<PathBuf> {
+    (1..=keep).map(|i| with_suffix(path, &format!(".{}", i))).collect()
+}
+
+/// Shifts existing backups down by one and copies the current `path` into `path.1`.
+/// Call before overwriting `path`. Does nothing if `path` doesn't exist yet.
+pub fn rotate_backups(path: &Path, keep: usize) -> io::Result<()> {
+    if keep == 0 || !path.exists() {
+        return Ok(());
+    }
+    let backups = backup_paths(path, keep);
+    for i in (1..keep).rev() {
+        if backups[i - 1].exists() {
+            std::fs::rename(&backups[i - 1], &backups[i])?;
+        }
+    }
+    std::fs::copy(path, &backups[0])?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_atomic_replaces_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.json");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        assert!(!dir.path().join("file.json.tmp").exists());
+    }
+
+    #[test]
+    fn rotate_keeps_newest_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("save.json");
+
+        for i in 0..5 {
+            rotate_backups(&path, 3).unwrap();
+            write_atomic(&path, format!("save {}", i).as_bytes()).unwrap();
+        }
+
+        let backups = backup_paths(&path, 3);
+        let read = |p: &PathBuf| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "save 4");
+        assert_eq!(read(&backups[0]), "save 3");
+        assert_eq!(read(&backups[1]), "save 2");
+        assert_eq!(read(&backups[2]), "save 1");
+        assert!(!dir.path().join("save.json.4").exists());
+    }
+
+    #[test]
+    fn rotate_without_original_is_noop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("missing.json");
+
+        rotate_backups(&path, 3).unwrap();
+        assert!(backup_paths(&path, 3).iter().all(|p| !p.exists()));
+    }
+}
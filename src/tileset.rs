@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Image and tile indices of a level theme, read from `assets/tilesets/*.ron`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct TilesetData {
+    /// Path under `assets/`.
+    pub image: String,
+    pub tile_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    /// Platform tile for each neighbour mask, see `level::neighbour_mask`.
+    pub platform: [usize; 16],
+    pub spikes: usize,
+    pub exit: usize,
+}
+
+impl TilesetData {
+    /// Every tile a level can ask for has to be in the image grid.
+    pub fn validate(&self) -> Result<(), String> {
+        let tiles = self.columns as usize * self.rows as usize;
+        match self.platform.iter().chain([&self.spikes, &self.exit]).find(|index| **index >= tiles) {
+            Some(index) => Err(format!("tile {} is outside the {}x{} grid", index, self.columns, self.rows)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A loaded tileset, ready to make sprites from.
+#[derive(Debug, Clone)]
+pub struct Tileset {
+    pub data: TilesetData,
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+impl Tileset {
+    /// Tile `index` stretched to `size`. `tint` multiplies the image colors.
+    pub fn sprite(&self, index: usize, size: f32, tint: Color) -> Sprite {
+        Sprite {
+            color: tint,
+            custom_size: Some(Vec2::splat(size)),
+            ..Sprite::from_atlas_image(
+                self.image.clone(),
+                TextureAtlas {
+                    layout: self.layout.clone(),
+                    index,
+                },
+            )
+        }
+    }
+}
+
+/// Reads and checks the tileset at `path` under `assets/`.
+pub fn read_tileset_data(path: &str) -> Result<TilesetData, String> {
+    let full_path = format!("assets/{}", path);
+    let contents = std::fs::read_to_string(&full_path).map_err(|e| format!("tileset {}: {}", full_path, e))?;
+    let data: TilesetData = ron::from_str(&contents).map_err(|e| format!("tileset {}: {}", full_path, e))?;
+    data.validate().map_err(|e| format!("tileset {}: {}", full_path, e))?;
+    Ok(data)
+}
+
+fn load_tileset_data(path: &str) -> Option<TilesetData> {
+    read_tileset_data(path)
+        .map_err(|e| warn!("Failed to load {}", e))
+        .ok()
+}
+
+/// Tilesets loaded so far; `None` for files that couldn't be read, so they aren't retried.
+#[derive(Resource, Default)]
+struct TilesetCache(HashMap<String, Option<Tileset>>);
+
+/// Loads tilesets on first use. Levels reload on every resume, so each one is only parsed once.
+#[derive(SystemParam)]
+pub struct Tilesets<'w> {
+    cache: ResMut<'w, TilesetCache>,
+    asset_server: Res<'w, AssetServer>,
+    layouts: ResMut<'w, Assets<TextureAtlasLayout>>,
+}
+
+impl Tilesets<'_> {
+    /// The tileset at `path` under `assets/`, or `None` to fall back to colored rectangles.
+    pub fn get(&mut self, path: &str) -> Option<Tileset> {
+        if let Some(cached) = self.cache.0.get(path) {
+            return cached.clone();
+        }
+        let tileset = load_tileset_data(path).map(|data| {
+            let layout = TextureAtlasLayout::from_grid(
+                UVec2::splat(data.tile_size),
+                data.columns,
+                data.rows,
+                None,
+                None,
+            );
+            Tileset {
+                image: self.asset_server.load(&data.image),
+                layout: self.layouts.add(layout),
+                data,
+            }
+        });
+        self.cache.0.insert(path.to_string(), tileset.clone());
+        tileset
+    }
+}
+
+pub struct TilesetPlugin;
+
+impl Plugin for TilesetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TilesetCache>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_tilesets_are_valid() {
+        let dir = format!("{}/assets/tilesets", env!("CARGO_MANIFEST_DIR"));
+        let mut count = 0;
+        for entry in std::fs::read_dir(&dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "ron") {
+                continue;
+            }
+            let contents = std::fs::read_to_string(&path).unwrap();
+            let data: TilesetData =
+                ron::from_str(&contents).unwrap_or_else(|e| panic!("Failed to parse {}: {}", path.display(), e));
+
+            if let Err(e) = data.validate() {
+                panic!("{}: {}", path.display(), e);
+            }
+            let image = format!("{}/assets/{}", env!("CARGO_MANIFEST_DIR"), data.image);
+            assert!(std::path::Path::new(&image).exists(), "{} is missing", image);
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+
+    #[test]
+    fn tiles_outside_the_grid_are_refused() {
+        let mut data = TilesetData {
+            image: "tilesets/grass.png".to_string(),
+            tile_size: 16,
+            columns: 4,
+            rows: 5,
+            platform: [0; 16],
+            spikes: 16,
+            exit: 19,
+        };
+        assert!(data.validate().is_ok());
+        data.exit = 20;
+        assert!(data.validate().is_err());
+        data.exit = 0;
+        data.platform[15] = 99;
+        assert!(data.validate().is_err());
+    }
+}
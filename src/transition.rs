@@ -0,0 +1,288 @@
+use bevy::prelude::*;
+
+use crate::actions::{keys_label, Action, Actions, KeyBindings};
+use crate::fade::{fading_out, spawn_screen_fade, ScreenFade};
+use crate::fonts::UiFonts;
+use crate::level::{level_name, CurrentLevel, LevelCompleted};
+use crate::locale::Locale;
+use crate::player::DeathCounter;
+use crate::speedrun::{format_time, SpeedrunTimer};
+use crate::states::GameState;
+
+/// Fade into the transition screen and out of it into the next level.
+const FADE_DURATION: f32 = 0.4;
+/// Seconds before the next level starts without a key press.
+const AUTO_ADVANCE: f32 = 5.0;
+
+/// The level just finished, shown between it and the next one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelSummary {
+    pub index: usize,
+    pub deaths: usize,
+    pub time: f32,
+}
+
+/// Set when a level is completed or skipped. Transitions without one (restarts, replays)
+/// go straight back to Playing.
+#[derive(Resource, Debug, Default)]
+pub struct PendingSummary(pub Option<LevelSummary>);
+
+#[derive(Component)]
+struct AutoAdvance(Timer);
+
+#[derive(Component)]
+struct ContinuePrompt;
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        // PostUpdate, so the speedrun split for the level is already recorded
+        app.init_resource::<PendingSummary>()
+            .add_systems(PostUpdate, remember_summary)
+            .add_systems(OnEnter(GameState::LevelTransition), spawn_transition_screen)
+            .add_systems(OnEnter(GameState::Playing), clear_summary)
+            .add_systems(
+                Update,
+                continue_to_level
+                    .run_if(in_state(GameState::LevelTransition))
+                    .run_if(not(fading_out)),
+            );
+    }
+}
+
+fn remember_summary(
+    mut completed: MessageReader<LevelCompleted>,
+    counter: Res<DeathCounter>,
+    timer: Res<SpeedrunTimer>,
+    mut pending: ResMut<PendingSummary>,
+) {
+    for event in completed.read() {
+        pending.0 = Some(LevelSummary {
+            index: event.index,
+            deaths: counter.current_level,
+            time: timer.splits.last().copied().unwrap_or_default(),
+        });
+    }
+}
+
+/// A summary left over from the last level of the run must not show up on a later restart.
+fn clear_summary(mut pending: ResMut<PendingSummary>) {
+    pending.0 = None;
+}
+
+fn prompt_text(locale: &Locale, bindings: &KeyBindings, remaining: f32) -> String {
+    locale.format(
+        "transition.continue",
+        &[
+            ("key", &keys_label(bindings.keys(Action::Confirm))),
+            ("seconds", &remaining.ceil()),
+        ],
+    )
+}
+
+fn spawn_transition_screen(
+    mut commands: Commands,
+    mut pending: ResMut<PendingSummary>,
+    current_level: Res<CurrentLevel>,
+    bindings: Res<KeyBindings>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(summary) = pending.0.take() else {
+        next_state.set(GameState::Playing);
+        return;
+    };
+    let next_name = level_name(current_level.0).unwrap_or_default();
+
+    commands
+        .spawn((
+            AutoAdvance(Timer::from_seconds(AUTO_ADVANCE, TimerMode::Once)),
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            DespawnOnExit::<GameState>(GameState::LevelTransition),
+        ))
+        .with_children(|parent| {
+            // The level just finished
+            parent.spawn((
+                Text::new(locale.format("transition.completed", &[("n", &(summary.index + 1))])),
+                fonts.title(36.0),
+                TextColor(Color::srgb(0.9, 0.8, 0.2)),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                Text::new(locale.format("transition.deaths", &[("count", &summary.deaths)])),
+                fonts.text(24.0),
+                TextColor(Color::WHITE),
+            ));
+            parent.spawn((
+                Text::new(locale.format("transition.time", &[("time", &format_time(summary.time))])),
+                fonts.text(24.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(50.0)),
+                    ..default()
+                },
+            ));
+
+            // The level coming up
+            parent.spawn((
+                Text::new(locale.format("transition.next", &[("n", &(current_level.0 + 1))])),
+                fonts.text(24.0),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+            parent.spawn((
+                Text::new(next_name),
+                fonts.title(48.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(50.0)),
+                    ..default()
+                },
+            ));
+
+            parent.spawn((
+                ContinuePrompt,
+                Text::new(prompt_text(&locale, &bindings, AUTO_ADVANCE)),
+                fonts.text(20.0),
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+        });
+
+    spawn_screen_fade(
+        &mut commands,
+        ScreenFade::from_black(FADE_DURATION),
+        GameState::LevelTransition,
+    );
+}
+
+/// Confirm, Jump or the countdown fades out into the next level.
+fn continue_to_level(
+    mut commands: Commands,
+    actions: Actions,
+    time: Res<Time>,
+    bindings: Res<KeyBindings>,
+    locale: Res<Locale>,
+    mut timers: Query<&mut AutoAdvance>,
+    mut prompts: Query<&mut Text, With<ContinuePrompt>>,
+) {
+    let Ok(mut auto_advance) = timers.single_mut() else {
+        return;
+    };
+    auto_advance.0.tick(time.delta());
+    for mut text in &mut prompts {
+        **text = prompt_text(&locale, &bindings, auto_advance.0.remaining_secs());
+    }
+
+    let pressed = actions.just_pressed(Action::Confirm) || actions.just_pressed(Action::Jump);
+    if pressed || auto_advance.0.is_finished() {
+        spawn_screen_fade(
+            &mut commands,
+            ScreenFade::to_black(FADE_DURATION, GameState::Playing),
+            GameState::LevelTransition,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+    use crate::fade::FadePlugin;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, bevy::input::InputPlugin, FadePlugin, TransitionPlugin));
+        app.init_state::<GameState>();
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<Locale>();
+        app.init_resource::<UiFonts>();
+        app.init_resource::<DeathCounter>();
+        app.init_resource::<SpeedrunTimer>();
+        app.insert_resource(CurrentLevel(1));
+        app.add_message::<LevelCompleted>();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(100)));
+        app
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world().resource::<State<GameState>>().get().clone()
+    }
+
+    #[test]
+    fn completed_level_is_summarised() {
+        let mut app = app();
+        app.insert_resource(DeathCounter {
+            current_level: 3,
+            total: 7,
+        });
+        app.insert_resource(SpeedrunTimer {
+            splits: vec![12.5],
+            ..default()
+        });
+        app.world_mut().write_message(LevelCompleted {
+            index: 0,
+            assisted: false,
+        });
+        app.update();
+
+        let pending = app.world().resource::<PendingSummary>();
+        assert_eq!(
+            pending.0,
+            Some(LevelSummary {
+                index: 0,
+                deaths: 3,
+                time: 12.5,
+            })
+        );
+    }
+
+    #[test]
+    fn restarts_skip_the_screen() {
+        let mut app = app();
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::LevelTransition);
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Playing);
+    }
+
+    #[test]
+    fn screen_advances_on_its_own() {
+        let mut app = app();
+        app.insert_resource(PendingSummary(Some(LevelSummary {
+            index: 0,
+            deaths: 0,
+            time: 10.0,
+        })));
+        app.world_mut().resource_mut::<NextState<GameState>>().set(GameState::LevelTransition);
+        app.update();
+        assert_eq!(state(&app), GameState::LevelTransition);
+        assert!(app.world().resource::<PendingSummary>().0.is_none());
+
+        for _ in 0..20 {
+            app.update();
+        }
+        assert_eq!(state(&app), GameState::LevelTransition);
+
+        // The rest of the countdown, the fade to black, then the level
+        let ticks = ((AUTO_ADVANCE + FADE_DURATION) * 10.0) as usize - 20 + 3;
+        for _ in 0..ticks {
+            app.update();
+        }
+        assert_eq!(state(&app), GameState::Playing);
+    }
+}
@@ -1,6 +1,10 @@
 use bevy::prelude::*;
 
+use crate::actions::{Action, Actions};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
 use crate::player::DeathCounter;
+use crate::speedrun::{format_time, SpeedrunTimer};
 use crate::states::GameState;
 
 #[derive(Component)]
@@ -18,20 +22,34 @@ impl Plugin for VictoryPlugin {
     }
 }
 
-fn death_comment(total: usize) -> &'static str {
+/// Locale key of the joke after the death count, if the count deserves one.
+fn death_comment(total: usize) -> Option<&'static str> {
     match total {
-        0 => " - flawless!",
-        69 => " - nice",
-        42 => " - the answer!",
-        100 => " - centurion!",
-        _ if total > 200 => " - respect for perseverance!",
-        _ => "",
+        0 => Some("victory.comment.flawless"),
+        69 => Some("victory.comment.nice"),
+        42 => Some("victory.comment.answer"),
+        100 => Some("victory.comment.centurion"),
+        _ if total > 200 => Some("victory.comment.perseverance"),
+        _ => None,
     }
 }
 
-fn spawn_victory_screen(mut commands: Commands, counter: Res<DeathCounter>) {
+fn spawn_victory_screen(
+    mut commands: Commands,
+    counter: Res<DeathCounter>,
+    timer: Res<SpeedrunTimer>,
+    locale: Res<Locale>,
+    fonts: Res<UiFonts>,
+) {
     let total = counter.total;
-    let comment = death_comment(total);
+    let comment = death_comment(total).map(|key| locale.get(key)).unwrap_or_default();
+    let record = if timer.run_record { locale.get("victory.new_record") } else { "" };
+    let level_records = if timer.level_records.is_empty() {
+        String::new()
+    } else {
+        let levels: Vec<String> = timer.level_records.iter().map(|i| (i + 1).to_string()).collect();
+        locale.format("victory.level_records", &[("levels", &levels.join(", "))])
+    };
 
     commands
         .spawn((
@@ -48,11 +66,8 @@ fn spawn_victory_screen(mut commands: Commands, counter: Res<DeathCounter>) {
         .with_children(|parent| {
             // Title
             parent.spawn((
-                Text::new("CONGRATULATIONS!"),
-                TextFont {
-                    font_size: 60.0,
-                    ..default()
-                },
+                Text::new(locale.get("victory.title")),
+                fonts.title(60.0),
                 TextColor(Color::srgb(0.9, 0.8, 0.2)),
                 Node {
                     margin: UiRect::bottom(Val::Px(30.0)),
@@ -62,12 +77,47 @@ fn spawn_victory_screen(mut commands: Commands, counter: Res<DeathCounter>) {
 
             // Death count
             parent.spawn((
-                Text::new(format!("You died: {}{}", total, comment)),
-                TextFont {
-                    font_size: 32.0,
+                Text::new(locale.format("victory.deaths", &[("count", &total), ("comment", &comment)])),
+                fonts.text(32.0),
+                TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
                     ..default()
                 },
+            ));
+
+            // Run time
+            parent.spawn((
+                Text::new(locale.format(
+                    "victory.time",
+                    &[("time", &format_time(timer.run_time())), ("record", &record)],
+                )),
+                fonts.text(32.0),
                 TextColor(Color::WHITE),
+                Node {
+                    margin: UiRect::bottom(Val::Px(10.0)),
+                    ..default()
+                },
+            ));
+
+            // Assisted runs set no records, say why
+            if timer.assisted {
+                parent.spawn((
+                    Text::new(locale.get("victory.assisted")),
+                    fonts.text(22.0),
+                    TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                    Node {
+                        margin: UiRect::bottom(Val::Px(10.0)),
+                        ..default()
+                    },
+                ));
+            }
+
+            // Per-level records
+            parent.spawn((
+                Text::new(level_records),
+                fonts.text(22.0),
+                TextColor(Color::srgb(0.9, 0.8, 0.2)),
                 Node {
                     margin: UiRect::bottom(Val::Px(40.0)),
                     ..default()
@@ -90,11 +140,8 @@ fn spawn_victory_screen(mut commands: Commands, counter: Res<DeathCounter>) {
                 ))
                 .with_children(|btn| {
                     btn.spawn((
-                        Text::new("Main Menu"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..default()
-                        },
+                        Text::new(locale.get("victory.main_menu")),
+                        fonts.text(24.0),
                         TextColor(Color::WHITE),
                     ));
                 });
@@ -102,16 +149,16 @@ fn spawn_victory_screen(mut commands: Commands, counter: Res<DeathCounter>) {
 }
 
 fn victory_action(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     mut next_state: ResMut<NextState<GameState>>,
-    mut commands: Commands,
+    mut counter: ResMut<DeathCounter>,
     buttons: Query<&Interaction, (With<VictoryAction>, Changed<Interaction>)>,
 ) {
-    let enter = keyboard.just_pressed(KeyCode::Enter);
+    let enter = actions.just_pressed(Action::Confirm);
     let clicked = buttons.iter().any(|i| *i == Interaction::Pressed);
 
     if enter || clicked {
-        commands.remove_resource::<DeathCounter>();
+        *counter = DeathCounter::default();
         next_state.set(GameState::Menu);
     }
 }
@@ -122,14 +169,23 @@ mod tests {
 
     #[test]
     fn death_comment_special_values() {
-        assert_eq!(death_comment(0), " - flawless!");
-        assert_eq!(death_comment(69), " - nice");
-        assert_eq!(death_comment(42), " - the answer!");
-        assert_eq!(death_comment(100), " - centurion!");
-        assert_eq!(death_comment(10), "");
-        assert_eq!(death_comment(201), " - respect for perseverance!");
-        assert_eq!(death_comment(500), " - respect for perseverance!");
+        assert_eq!(death_comment(0), Some("victory.comment.flawless"));
+        assert_eq!(death_comment(69), Some("victory.comment.nice"));
+        assert_eq!(death_comment(42), Some("victory.comment.answer"));
+        assert_eq!(death_comment(100), Some("victory.comment.centurion"));
+        assert_eq!(death_comment(10), None);
+        assert_eq!(death_comment(201), Some("victory.comment.perseverance"));
+        assert_eq!(death_comment(500), Some("victory.comment.perseverance"));
         // 200 exactly should not trigger the >200 branch
-        assert_eq!(death_comment(200), "");
+        assert_eq!(death_comment(200), None);
+    }
+
+    #[test]
+    fn death_comments_are_translated() {
+        let locale = Locale::default();
+        for total in [0, 42, 69, 100, 201] {
+            let key = death_comment(total).unwrap();
+            assert_ne!(locale.get(key), key, "{} missing from en.ron", key);
+        }
     }
 }
@@ -0,0 +1,1033 @@
+use bevy::prelude::*;
+
+use crate::actions::{Action, Actions};
+use crate::fonts::UiFonts;
+use crate::locale::Locale;
+use crate::states::GameState;
+
+pub const COLOR_SELECTED: Color = Color::srgb(0.3, 0.3, 0.7);
+pub const COLOR_NORMAL: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const COLOR_DANGER: Color = Color::srgb(0.7, 0.15, 0.15);
+const COLOR_HIGH_CONTRAST_SELECTED: Color = Color::srgb(1.0, 0.85, 0.0);
+
+/// Slider step used by `MenuEntry::slider`.
+const SLIDER_STEP: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MenuLayout {
+    /// Up/Down move the selection, Left/Right adjust the selected widget.
+    #[default]
+    Vertical,
+    /// Left/Right move the selection. Used for dialog buttons.
+    Horizontal,
+    /// Rows of `columns` items: Up/Down change row, Left/Right move within it.
+    /// The last row may be shorter.
+    Grid { columns: usize },
+}
+
+/// A list of items navigated together. Only the focused menu reacts to input.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Menu {
+    pub selected: usize,
+    pub layout: MenuLayout,
+}
+
+/// Open menus in the order they were spawned. The last one has focus, so a
+/// modal spawned on top of a screen traps input until it is closed.
+#[derive(Resource, Debug, Default)]
+pub struct MenuFocus(Vec<Entity>);
+
+impl MenuFocus {
+    pub fn focused(&self) -> Option<Entity> {
+        self.0.last().copied()
+    }
+}
+
+/// One selectable entry of `menu`, in position `index`.
+#[derive(Component, Debug, Clone)]
+pub struct MenuItem {
+    pub menu: Entity,
+    pub index: usize,
+    pub label: String,
+}
+
+/// Background of an item when it is and isn't selected.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ItemColors {
+    pub normal: Color,
+    pub selected: Color,
+}
+
+impl Default for ItemColors {
+    fn default() -> Self {
+        Self {
+            normal: COLOR_NORMAL,
+            selected: COLOR_SELECTED,
+        }
+    }
+}
+
+/// Text color an item label was spawned with, restored when high contrast is turned off.
+#[derive(Component, Debug, Clone, Copy)]
+struct LabelColor(Color);
+
+/// Colors shared by every menu. High contrast draws items black with a white
+/// border and the selected one yellow with black text, ignoring `ItemColors`.
+#[derive(Resource, Debug, Clone, Copy, Default)]
+pub struct UiTheme {
+    pub high_contrast: bool,
+}
+
+/// What an item does besides being selected.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub enum Widget {
+    Button,
+    /// Value in 0..=1, shown as a bar.
+    Slider { value: f32, step: f32 },
+    /// `texts` are shown when off and on.
+    Toggle { on: bool, texts: [String; 2] },
+    /// One of `options`, cycled with Left/Right.
+    Choice { options: Vec<String>, index: usize },
+}
+
+impl Widget {
+    /// Left (-1) or Right (1) on the item. Returns true if the value changed.
+    pub fn adjust(&mut self, delta: i32) -> bool {
+        match self {
+            Widget::Button => false,
+            Widget::Slider { value, step } => {
+                // Snap to the step so repeated presses don't drift
+                let new = ((*value + *step * delta as f32) / *step).round() * *step;
+                let new = new.clamp(0.0, 1.0);
+                let changed = new != *value;
+                *value = new;
+                changed
+            }
+            Widget::Toggle { on, .. } => {
+                *on = !*on;
+                true
+            }
+            Widget::Choice { options, index } => {
+                if options.len() < 2 {
+                    return false;
+                }
+                let len = options.len() as i32;
+                *index = (*index as i32 + delta).rem_euclid(len) as usize;
+                true
+            }
+        }
+    }
+
+    /// Confirm on the item. Toggles flip and choices step forward; returns true if the value changed.
+    fn activate(&mut self) -> bool {
+        match self {
+            Widget::Toggle { .. } | Widget::Choice { .. } => self.adjust(1),
+            Widget::Button | Widget::Slider { .. } => false,
+        }
+    }
+
+    pub fn text(&self, label: &str) -> String {
+        match self {
+            Widget::Button => label.to_string(),
+            Widget::Slider { value, .. } => format!("{}: {}", label, slider_bar(*value)),
+            Widget::Toggle { on, texts } => format!("{}: {}", label, texts[*on as usize]),
+            Widget::Choice { options, index } => {
+                format!("{}: {}", label, options.get(*index).map(String::as_str).unwrap_or("-"))
+            }
+        }
+    }
+}
+
+fn slider_bar(value: f32) -> String {
+    let filled = ((value * 10.0).round() as usize).min(10);
+    let empty = 10 - filled;
+    let bar: String = "#".repeat(filled) + &"-".repeat(empty);
+    format!("{} {}%", bar, (value * 100.0).round() as u32)
+}
+
+/// Marks the root of a modal dialog. Back closes it.
+#[derive(Component)]
+pub struct Modal;
+
+/// Asks the player something in a modal. The answer comes back as a
+/// `DialogClosed` with the same `id`.
+#[derive(Message, Debug, Clone, PartialEq)]
+pub struct OpenDialog {
+    pub id: &'static str,
+    pub title: String,
+    pub message: String,
+    pub buttons: Vec<String>,
+    /// Button selected when the dialog opens.
+    pub default: usize,
+    /// Button drawn red while selected, for choices that destroy something.
+    pub danger: Option<usize>,
+}
+
+impl OpenDialog {
+    /// Yes/No question: "Yes" is button 0, "No" is selected for safety.
+    pub fn confirm(
+        id: &'static str,
+        title: impl Into<String>,
+        message: impl Into<String>,
+        locale: &Locale,
+    ) -> Self {
+        Self {
+            id,
+            title: title.into(),
+            message: message.into(),
+            buttons: vec![locale.get("dialog.yes").to_string(), locale.get("dialog.no").to_string()],
+            default: 1,
+            danger: Some(0),
+        }
+    }
+}
+
+/// Answer to an `OpenDialog`: the pressed button, or `None` if Back dismissed it.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DialogClosed {
+    pub id: &'static str,
+    pub choice: Option<usize>,
+}
+
+impl DialogClosed {
+    /// "Yes" was pressed on the `confirm` dialog `id`.
+    pub fn confirmed(&self, id: &'static str) -> bool {
+        self.id == id && self.choice == Some(0)
+    }
+}
+
+/// Closes the dialog `id` without an answer, e.g. when a countdown runs out.
+/// It reports `DialogClosed` with `choice: None`, like Back.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloseDialog(pub &'static str);
+
+/// Root of a dialog opened through `OpenDialog`.
+#[derive(Component)]
+struct Dialog(&'static str);
+
+#[derive(Component)]
+struct DialogButton(usize);
+
+/// Message line of a modal, for screens that keep it up to date.
+#[derive(Component)]
+pub struct ModalMessage;
+
+/// What happened in the focused menu this frame.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuEvent {
+    /// A button was confirmed or clicked.
+    Activated { menu: Entity, item: Entity },
+    /// A slider, toggle or choice changed its value.
+    Changed { menu: Entity, item: Entity },
+    /// Back was pressed on a screen menu. Modals close instead.
+    Back { menu: Entity },
+}
+
+/// Menu systems read input before screens respond to `MenuEvent`s, and redraw after.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MenuSystems {
+    Navigate,
+    Respond,
+    Display,
+}
+
+/// Size and spacing of the items spawned by `spawn_menu`. Entries can override the width.
+#[derive(Debug, Clone, Copy)]
+pub struct MenuStyle {
+    pub layout: MenuLayout,
+    pub item_width: f32,
+    pub item_height: f32,
+    pub font_size: f32,
+    pub spacing: f32,
+}
+
+impl Default for MenuStyle {
+    fn default() -> Self {
+        Self {
+            layout: MenuLayout::Vertical,
+            item_width: 300.0,
+            item_height: 50.0,
+            font_size: 24.0,
+            spacing: 10.0,
+        }
+    }
+}
+
+/// One item of a menu as passed to `spawn_menu`. `tag` is the screen's own
+/// component, used to tell items apart when a `MenuEvent` arrives.
+pub struct MenuEntry<T> {
+    tag: T,
+    label: String,
+    widget: Widget,
+    colors: ItemColors,
+    text_color: Color,
+    width: Option<f32>,
+    gap_before: f32,
+    selected: bool,
+}
+
+impl<T: Component> MenuEntry<T> {
+    pub fn button(tag: T, label: impl Into<String>) -> Self {
+        Self {
+            tag,
+            label: label.into(),
+            widget: Widget::Button,
+            colors: ItemColors::default(),
+            text_color: Color::WHITE,
+            width: None,
+            gap_before: 0.0,
+            selected: false,
+        }
+    }
+
+    pub fn slider(tag: T, label: impl Into<String>, value: f32) -> Self {
+        Self::button(tag, label).widget(Widget::Slider {
+            value,
+            step: SLIDER_STEP,
+        })
+    }
+
+    pub fn toggle(tag: T, label: impl Into<String>, on: bool, texts: [&str; 2]) -> Self {
+        Self::button(tag, label).widget(Widget::Toggle {
+            on,
+            texts: texts.map(str::to_string),
+        })
+    }
+
+    pub fn choice(tag: T, label: impl Into<String>, options: Vec<String>, index: usize) -> Self {
+        Self::button(tag, label).widget(Widget::Choice { options, index })
+    }
+
+    fn widget(mut self, widget: Widget) -> Self {
+        self.widget = widget;
+        self
+    }
+
+    pub fn colors(mut self, normal: Color, selected: Color) -> Self {
+        self.colors = ItemColors { normal, selected };
+        self
+    }
+
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    /// Width instead of `MenuStyle::item_width`.
+    pub fn width(mut self, px: f32) -> Self {
+        self.width = Some(px);
+        self
+    }
+
+    /// Extra space above the item, to set it apart from the rest.
+    pub fn gap_before(mut self, px: f32) -> Self {
+        self.gap_before = px;
+        self
+    }
+
+    /// Selected when the menu opens instead of the first item.
+    pub fn selected(mut self) -> Self {
+        self.selected = true;
+        self
+    }
+}
+
+pub struct WidgetsPlugin;
+
+impl Plugin for WidgetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>()
+            .init_resource::<UiTheme>()
+            .add_message::<MenuEvent>()
+            .add_message::<OpenDialog>()
+            .add_message::<DialogClosed>()
+            .add_message::<CloseDialog>()
+            .add_observer(focus_opened_menu)
+            .add_observer(forget_closed_menu)
+            .configure_sets(
+                Update,
+                (MenuSystems::Navigate, MenuSystems::Respond, MenuSystems::Display).chain(),
+            )
+            .add_systems(
+                Update,
+                (menu_navigation, menu_mouse, menu_activate, answer_dialogs, close_dialogs)
+                    .chain()
+                    .in_set(MenuSystems::Navigate),
+            )
+            .add_systems(
+                Update,
+                (open_dialogs, menu_highlight, menu_text)
+                    .chain()
+                    .in_set(MenuSystems::Display),
+            );
+    }
+}
+
+/// Spawns `entries` as a list under `parent` and returns the menu entity.
+pub fn spawn_menu<T: Component>(
+    parent: &mut ChildSpawnerCommands,
+    fonts: &UiFonts,
+    style: MenuStyle,
+    entries: Vec<MenuEntry<T>>,
+) -> Entity {
+    let selected = entries.iter().position(|e| e.selected).unwrap_or(0);
+    let (flex_direction, row_gap, column_gap) = match style.layout {
+        MenuLayout::Vertical => (FlexDirection::Column, Val::Px(style.spacing), Val::ZERO),
+        MenuLayout::Horizontal => (FlexDirection::Row, Val::ZERO, Val::Px(style.spacing)),
+        MenuLayout::Grid { .. } => (FlexDirection::Row, Val::Px(style.spacing), Val::Px(style.spacing)),
+    };
+    // A grid wraps after its first row, so it is as wide as that row
+    let (flex_wrap, width) = match style.layout {
+        MenuLayout::Grid { columns } => {
+            let row: f32 = entries
+                .iter()
+                .take(columns)
+                .map(|e| e.width.unwrap_or(style.item_width))
+                .sum();
+            let gaps = style.spacing * columns.saturating_sub(1) as f32;
+            (FlexWrap::Wrap, Val::Px(row + gaps))
+        }
+        _ => (FlexWrap::NoWrap, Val::Auto),
+    };
+
+    let mut menu = parent.spawn((
+        Menu {
+            selected,
+            layout: style.layout,
+        },
+        Node {
+            flex_direction,
+            flex_wrap,
+            width,
+            align_items: AlignItems::Center,
+            row_gap,
+            column_gap,
+            ..default()
+        },
+    ));
+    let menu_entity = menu.id();
+
+    menu.with_children(|list| {
+        for (index, entry) in entries.into_iter().enumerate() {
+            let text = entry.widget.text(&entry.label);
+            list.spawn((
+                Button,
+                entry.tag,
+                MenuItem {
+                    menu: menu_entity,
+                    index,
+                    label: entry.label,
+                },
+                entry.widget,
+                entry.colors,
+                Node {
+                    width: Val::Px(entry.width.unwrap_or(style.item_width)),
+                    height: Val::Px(style.item_height),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    margin: UiRect::top(Val::Px(entry.gap_before)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(entry.colors.normal),
+                BorderColor::all(Color::NONE),
+            ))
+            .with_children(|btn| {
+                btn.spawn((
+                    Text::new(text),
+                    fonts.text(style.font_size),
+                    TextColor(entry.text_color),
+                    LabelColor(entry.text_color),
+                ));
+            });
+        }
+    });
+
+    menu_entity
+}
+
+/// Spawns a dialog over the screen with a row of `buttons`. `root` is added to
+/// the overlay entity, e.g. a marker to find it again or `DespawnOnExit`.
+pub fn spawn_modal<T: Component>(
+    commands: &mut Commands,
+    fonts: &UiFonts,
+    root: impl Bundle,
+    title: &str,
+    message: &str,
+    buttons: Vec<MenuEntry<T>>,
+) -> Entity {
+    commands
+        .spawn((
+            Modal,
+            root,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            GlobalZIndex(200),
+        ))
+        .with_children(|overlay| {
+            overlay
+                .spawn((
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(40.0)),
+                        border_radius: BorderRadius::all(Val::Px(12.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.12, 0.12, 0.12)),
+                ))
+                .with_children(|panel| {
+                    panel.spawn((
+                        Text::new(title),
+                        fonts.title(32.0),
+                        TextColor(Color::WHITE),
+                        Node { margin: UiRect::bottom(Val::Px(10.0)), ..default() },
+                    ));
+                    if !message.is_empty() {
+                        panel.spawn((
+                            ModalMessage,
+                            Text::new(message),
+                            fonts.text(18.0),
+                            TextColor(Color::srgb(0.8, 0.4, 0.4)),
+                            Node { margin: UiRect::bottom(Val::Px(10.0)), ..default() },
+                        ));
+                    }
+                    let style = MenuStyle {
+                        layout: MenuLayout::Horizontal,
+                        item_width: 120.0,
+                        item_height: 45.0,
+                        font_size: 22.0,
+                        spacing: 20.0,
+                    };
+                    panel
+                        .spawn(Node {
+                            margin: UiRect::top(Val::Px(20.0)),
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            spawn_menu(row, fonts, style, buttons);
+                        });
+                });
+        })
+        .id()
+}
+
+fn focus_opened_menu(add: On<Add, Menu>, mut focus: ResMut<MenuFocus>) {
+    focus.0.push(add.entity);
+}
+
+fn forget_closed_menu(remove: On<Remove, Menu>, mut focus: ResMut<MenuFocus>) {
+    focus.0.retain(|e| *e != remove.entity);
+}
+
+fn item_count(items: &Query<&MenuItem>, menu: Entity) -> usize {
+    items.iter().filter(|item| item.menu == menu).count()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn menu_navigation(
+    mut commands: Commands,
+    actions: Actions,
+    focus: Res<MenuFocus>,
+    mut menus: Query<&mut Menu>,
+    items: Query<&MenuItem>,
+    parents: Query<&ChildOf>,
+    modals: Query<Option<&Dialog>, With<Modal>>,
+    mut events: MessageWriter<MenuEvent>,
+    mut closed: MessageWriter<DialogClosed>,
+) {
+    let Some(menu_entity) = focus.focused() else {
+        return;
+    };
+    let Ok(mut menu) = menus.get_mut(menu_entity) else {
+        return;
+    };
+
+    if actions.just_pressed(Action::Back) {
+        match parents.iter_ancestors(menu_entity).find(|e| modals.contains(*e)) {
+            Some(modal) => {
+                if let Ok(Some(dialog)) = modals.get(modal) {
+                    closed.write(DialogClosed {
+                        id: dialog.0,
+                        choice: None,
+                    });
+                }
+                commands.entity(modal).despawn();
+            }
+            None => {
+                events.write(MenuEvent::Back { menu: menu_entity });
+            }
+        }
+        return;
+    }
+
+    let len = item_count(&items, menu_entity);
+    if len == 0 {
+        return;
+    }
+    let current = menu.selected.min(len - 1);
+    let (prev, next) = match menu.layout {
+        MenuLayout::Vertical => (Action::MenuUp, Action::MenuDown),
+        MenuLayout::Horizontal => (Action::MenuLeft, Action::MenuRight),
+        MenuLayout::Grid { columns } => {
+            menu.selected = grid_step(&actions, current, len, columns.max(1));
+            return;
+        }
+    };
+    if actions.just_pressed(prev) {
+        menu.selected = (current + len - 1) % len;
+    }
+    if actions.just_pressed(next) {
+        menu.selected = (current + 1) % len;
+    }
+}
+
+/// Moving onto a shorter row lands on its last item.
+fn grid_step(actions: &Actions, current: usize, len: usize, columns: usize) -> usize {
+    let rows = len.div_ceil(columns);
+    let (mut row, mut col) = (current / columns, current % columns);
+    if actions.just_pressed(Action::MenuUp) {
+        row = (row + rows - 1) % rows;
+    }
+    if actions.just_pressed(Action::MenuDown) {
+        row = (row + 1) % rows;
+    }
+    let row_len = (len - row * columns).min(columns);
+    col = col.min(row_len - 1);
+    if actions.just_pressed(Action::MenuLeft) {
+        col = (col + row_len - 1) % row_len;
+    }
+    if actions.just_pressed(Action::MenuRight) {
+        col = (col + 1) % row_len;
+    }
+    row * columns + col
+}
+
+fn menu_mouse(
+    focus: Res<MenuFocus>,
+    mut menus: Query<&mut Menu>,
+    buttons: Query<(&MenuItem, &Interaction), Changed<Interaction>>,
+) {
+    let Some(menu_entity) = focus.focused() else {
+        return;
+    };
+    let Ok(mut menu) = menus.get_mut(menu_entity) else {
+        return;
+    };
+    for (item, interaction) in &buttons {
+        let hovered = *interaction == Interaction::Hovered || *interaction == Interaction::Pressed;
+        if hovered && item.menu == menu_entity {
+            menu.selected = item.index;
+        }
+    }
+}
+
+fn menu_activate(
+    actions: Actions,
+    focus: Res<MenuFocus>,
+    menus: Query<&Menu>,
+    mut items: Query<(Entity, &MenuItem, &mut Widget)>,
+    clicked: Query<(Entity, &MenuItem, &Interaction), Changed<Interaction>>,
+    mut events: MessageWriter<MenuEvent>,
+) {
+    let Some(menu_entity) = focus.focused() else {
+        return;
+    };
+    let Ok(menu) = menus.get(menu_entity) else {
+        return;
+    };
+
+    let selected_item = items
+        .iter()
+        .find(|(_, item, _)| item.menu == menu_entity && item.index == menu.selected)
+        .map(|(entity, _, _)| entity);
+    let clicked_item = clicked
+        .iter()
+        .find(|(_, item, i)| item.menu == menu_entity && **i == Interaction::Pressed)
+        .map(|(entity, _, _)| entity);
+    let confirmed = clicked_item.or(selected_item.filter(|_| actions.just_pressed(Action::Confirm)));
+
+    if let Some(entity) = confirmed {
+        let Ok((_, _, mut widget)) = items.get_mut(entity) else {
+            return;
+        };
+        if *widget == Widget::Button {
+            events.write(MenuEvent::Activated {
+                menu: menu_entity,
+                item: entity,
+            });
+        } else if widget.activate() {
+            events.write(MenuEvent::Changed {
+                menu: menu_entity,
+                item: entity,
+            });
+        }
+        return;
+    }
+
+    if menu.layout != MenuLayout::Vertical {
+        return;
+    }
+    let delta = actions.just_pressed(Action::MenuRight) as i32 - actions.just_pressed(Action::MenuLeft) as i32;
+    if delta == 0 {
+        return;
+    }
+    let Some(entity) = selected_item else {
+        return;
+    };
+    let Ok((_, _, mut widget)) = items.get_mut(entity) else {
+        return;
+    };
+    if widget.adjust(delta) {
+        events.write(MenuEvent::Changed {
+            menu: menu_entity,
+            item: entity,
+        });
+    }
+}
+
+/// Dialogs close with their state, so one left open can't outlive its screen.
+fn open_dialogs(
+    mut commands: Commands,
+    mut requests: MessageReader<OpenDialog>,
+    state: Option<Res<State<GameState>>>,
+    fonts: Res<UiFonts>,
+) {
+    for request in requests.read() {
+        let buttons = request
+            .buttons
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                let mut entry = MenuEntry::button(DialogButton(i), label.clone());
+                if request.danger == Some(i) {
+                    entry = entry.colors(COLOR_NORMAL, COLOR_DANGER);
+                }
+                if request.default == i {
+                    entry = entry.selected();
+                }
+                entry
+            })
+            .collect();
+        let root = spawn_modal(
+            &mut commands,
+            &fonts,
+            Dialog(request.id),
+            &request.title,
+            &request.message,
+            buttons,
+        );
+        if let Some(state) = &state {
+            commands
+                .entity(root)
+                .insert(DespawnOnExit::<GameState>(state.get().clone()));
+        }
+    }
+}
+
+fn answer_dialogs(
+    mut commands: Commands,
+    mut events: MessageReader<MenuEvent>,
+    buttons: Query<&DialogButton>,
+    parents: Query<&ChildOf>,
+    dialogs: Query<&Dialog>,
+    mut closed: MessageWriter<DialogClosed>,
+) {
+    for event in events.read() {
+        let MenuEvent::Activated { menu, item } = event else {
+            continue;
+        };
+        let Ok(button) = buttons.get(*item) else {
+            continue;
+        };
+        let Some(root) = parents.iter_ancestors(*menu).find(|e| dialogs.contains(*e)) else {
+            continue;
+        };
+        let Ok(dialog) = dialogs.get(root) else {
+            continue;
+        };
+        closed.write(DialogClosed {
+            id: dialog.0,
+            choice: Some(button.0),
+        });
+        commands.entity(root).despawn();
+    }
+}
+
+fn close_dialogs(
+    mut commands: Commands,
+    mut requests: MessageReader<CloseDialog>,
+    dialogs: Query<(Entity, &Dialog)>,
+    mut closed: MessageWriter<DialogClosed>,
+) {
+    for request in requests.read() {
+        for (root, dialog) in &dialogs {
+            if dialog.0 == request.0 {
+                closed.write(DialogClosed {
+                    id: dialog.0,
+                    choice: None,
+                });
+                commands.entity(root).despawn();
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn menu_highlight(
+    theme: Res<UiTheme>,
+    menus: Query<&Menu>,
+    mut items: Query<(&MenuItem, &ItemColors, &Children, &mut BackgroundColor, &mut BorderColor)>,
+    mut labels: Query<(&LabelColor, &mut TextColor)>,
+) {
+    for (item, colors, children, mut bg, mut border) in &mut items {
+        let is_selected = menus.get(item.menu).is_ok_and(|m| m.selected == item.index);
+        let (background, edge, text) = match (theme.high_contrast, is_selected) {
+            (false, true) => (colors.selected, Color::NONE, None),
+            (false, false) => (colors.normal, Color::NONE, None),
+            (true, true) => (COLOR_HIGH_CONTRAST_SELECTED, COLOR_HIGH_CONTRAST_SELECTED, Some(Color::BLACK)),
+            (true, false) => (Color::BLACK, Color::WHITE, Some(Color::WHITE)),
+        };
+        *bg = BackgroundColor(background);
+        *border = BorderColor::all(edge);
+        for child in children.iter() {
+            if let Ok((label, mut color)) = labels.get_mut(child) {
+                color.0 = text.unwrap_or(label.0);
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn menu_text(
+    items: Query<(&MenuItem, &Widget, &Children), Or<(Changed<MenuItem>, Changed<Widget>)>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (item, widget, children) in &items {
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                **text = widget.text(&item.label);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::actions::KeyBindings;
+
+    #[derive(Component)]
+    struct TestItem;
+
+    fn menu_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins);
+        app.init_resource::<ButtonInput<KeyCode>>();
+        app.init_resource::<KeyBindings>();
+        app.init_resource::<UiFonts>();
+        app.add_plugins(WidgetsPlugin);
+        app
+    }
+
+    fn entries(count: usize) -> Vec<MenuEntry<TestItem>> {
+        (0..count).map(|i| MenuEntry::button(TestItem, format!("Item {}", i))).collect()
+    }
+
+    fn spawn_screen_menu(app: &mut App, count: usize) -> Entity {
+        let spawn = move |mut commands: Commands, fonts: Res<UiFonts>| {
+            let mut menu = Entity::PLACEHOLDER;
+            commands.spawn(Node::default()).with_children(|parent| {
+                menu = spawn_menu(parent, &fonts, MenuStyle::default(), entries(count));
+            });
+            menu
+        };
+        app.world_mut().run_system_once(spawn).unwrap()
+    }
+
+    fn spawn_dialog(app: &mut App) -> Entity {
+        let spawn = |mut commands: Commands, fonts: Res<UiFonts>| {
+            spawn_modal(&mut commands, &fonts, (), "Sure?", "", entries(2))
+        };
+        app.world_mut().run_system_once(spawn).unwrap()
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.clear();
+        keyboard.press(key);
+        app.update();
+        let mut keyboard = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keyboard.release(key);
+        keyboard.clear();
+    }
+
+    fn selected(app: &App, menu: Entity) -> usize {
+        app.world().get::<Menu>(menu).unwrap().selected
+    }
+
+    fn focused(app: &App) -> Option<Entity> {
+        app.world().resource::<MenuFocus>().focused()
+    }
+
+    fn drain_events(app: &mut App) -> Vec<MenuEvent> {
+        app.world_mut().resource_mut::<Messages<MenuEvent>>().drain().collect()
+    }
+
+    #[test]
+    fn navigation_wraps_around() {
+        let mut app = menu_app();
+        let menu = spawn_screen_menu(&mut app, 3);
+
+        press(&mut app, KeyCode::ArrowUp);
+        assert_eq!(selected(&app, menu), 2);
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 0);
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 0);
+    }
+
+    #[test]
+    fn grid_moves_by_rows_and_within_rows() {
+        let mut app = menu_app();
+        // Two rows of three, then one item alone
+        let spawn = |mut commands: Commands, fonts: Res<UiFonts>| {
+            let mut menu = Entity::PLACEHOLDER;
+            commands.spawn(Node::default()).with_children(|parent| {
+                let style = MenuStyle {
+                    layout: MenuLayout::Grid { columns: 3 },
+                    ..default()
+                };
+                menu = spawn_menu(parent, &fonts, style, entries(7));
+            });
+            menu
+        };
+        let menu = app.world_mut().run_system_once(spawn).unwrap();
+
+        press(&mut app, KeyCode::ArrowRight);
+        press(&mut app, KeyCode::ArrowRight);
+        assert_eq!(selected(&app, menu), 2);
+        press(&mut app, KeyCode::ArrowRight);
+        assert_eq!(selected(&app, menu), 0);
+        press(&mut app, KeyCode::ArrowLeft);
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 5);
+
+        // The short last row only has one column
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 6);
+        press(&mut app, KeyCode::ArrowRight);
+        assert_eq!(selected(&app, menu), 6);
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 0);
+        press(&mut app, KeyCode::ArrowUp);
+        assert_eq!(selected(&app, menu), 6);
+    }
+
+    #[test]
+    fn modal_traps_focus_until_closed() {
+        let mut app = menu_app();
+        let menu = spawn_screen_menu(&mut app, 3);
+        let dialog = spawn_dialog(&mut app);
+        let buttons = focused(&app).unwrap();
+        assert_ne!(buttons, menu);
+
+        press(&mut app, KeyCode::ArrowDown);
+        press(&mut app, KeyCode::ArrowRight);
+        assert_eq!(selected(&app, menu), 0);
+        assert_eq!(selected(&app, buttons), 1);
+
+        press(&mut app, KeyCode::Enter);
+        let events = drain_events(&mut app);
+        assert!(matches!(events.as_slice(), [MenuEvent::Activated { menu, .. }] if *menu == buttons));
+
+        // Back closes the dialog without reaching the screen
+        press(&mut app, KeyCode::Escape);
+        assert!(app.world().get_entity(dialog).is_err());
+        assert_eq!(focused(&app), Some(menu));
+        assert!(drain_events(&mut app).is_empty());
+
+        press(&mut app, KeyCode::ArrowDown);
+        assert_eq!(selected(&app, menu), 1);
+    }
+
+    fn drain_closed(app: &mut App) -> Vec<DialogClosed> {
+        app.world_mut().resource_mut::<Messages<DialogClosed>>().drain().collect()
+    }
+
+    #[test]
+    fn dialog_answers_with_pressed_button() {
+        let mut app = menu_app();
+        let locale = Locale::default();
+        spawn_screen_menu(&mut app, 2);
+
+        app.world_mut()
+            .write_message(OpenDialog::confirm("reset", "Reset?", "All progress will be lost.", &locale));
+        app.update();
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(
+            drain_closed(&mut app),
+            vec![DialogClosed {
+                id: "reset",
+                choice: Some(1),
+            }]
+        );
+
+        app.world_mut().write_message(OpenDialog::confirm("reset", "Reset?", "", &locale));
+        app.update();
+        press(&mut app, KeyCode::ArrowLeft);
+        press(&mut app, KeyCode::Enter);
+        let closed = drain_closed(&mut app);
+        assert!(closed[0].confirmed("reset"));
+
+        app.world_mut().write_message(OpenDialog::confirm("reset", "Reset?", "", &locale));
+        app.update();
+        press(&mut app, KeyCode::Escape);
+        assert_eq!(drain_closed(&mut app)[0].choice, None);
+        assert!(drain_events(&mut app).iter().all(|e| !matches!(e, MenuEvent::Back { .. })));
+        assert_eq!(app.world_mut().query::<&Dialog>().iter(app.world()).count(), 0);
+
+        // Closing from code answers like Back
+        app.world_mut().write_message(OpenDialog::confirm("reset", "Reset?", "", &locale));
+        app.update();
+        app.world_mut().write_message(CloseDialog("reset"));
+        app.update();
+        assert_eq!(drain_closed(&mut app)[0].choice, None);
+        assert_eq!(app.world_mut().query::<&Dialog>().iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn widgets_adjust_within_bounds() {
+        let mut slider = Widget::Slider { value: 0.9, step: 0.1 };
+        assert!(slider.adjust(1));
+        assert!(!slider.adjust(1));
+        assert_eq!(slider, Widget::Slider { value: 1.0, step: 0.1 });
+
+        let mut choice = Widget::Choice {
+            options: vec!["a".to_string(), "b".to_string()],
+            index: 0,
+        };
+        assert!(choice.adjust(-1));
+        assert_eq!(choice.text("Pick"), "Pick: b");
+
+        let mut toggle = Widget::Toggle { on: false, texts: ["Off".to_string(), "On".to_string()] };
+        assert!(toggle.activate());
+        assert_eq!(toggle.text("Ghost"), "Ghost: On");
+
+        // Out of range values still draw a full or empty bar
+        assert_eq!(slider_bar(1.5), "########## 150%");
+        assert_eq!(slider_bar(-0.5), "---------- 0%");
+    }
+}